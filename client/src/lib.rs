@@ -0,0 +1,387 @@
+//! Client for the `swap` program: instruction builders, PDA derivation, account
+//! deserializers and event decoders, built on the program crate's
+//! `no-entrypoint` build so every type comes straight from `swap`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{Discriminator, InstructionData};
+
+pub use swap::{self, ID};
+
+/// Program derived addresses, each returned with its bump.
+pub mod pda {
+    use super::*;
+
+    /// Intrachain offer, `seeds = [b"offer", maker, id]`.
+    pub fn offer(maker: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"offer", maker.as_ref(), &id.to_le_bytes()], &ID)
+    }
+
+    /// `InterchainOffer` or `InterchainOriginSOlOffer`, keyed by the Solana
+    /// seller, `seeds = [b"InterChainoffer", seller_sol, id]`.
+    pub fn interchain_offer(seller_sol: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"InterChainoffer", seller_sol.as_ref(), &id.to_le_bytes()],
+            &ID,
+        )
+    }
+
+    /// Legacy shared native vault, `seeds = [b"vault-native"]`.
+    pub fn vault_native() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"vault-native"], &ID)
+    }
+
+    /// Authority of the shared token vaults and signed-order delegations,
+    /// `seeds = [b"global-authority"]`.
+    pub fn global_authority() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"global-authority"], &ID)
+    }
+
+    /// Per-offer token escrow of an offer, interchain offer or HTLC.
+    pub fn escrow(offer: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"escrow", offer.as_ref()], &ID)
+    }
+
+    /// Per-offer lamport escrow of an interchain offer or HTLC.
+    pub fn escrow_native(offer: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"escrow-native", offer.as_ref()], &ID)
+    }
+
+    /// Escrow owed for `mint`; native SOL is keyed by the wrapped SOL mint.
+    pub fn vault_ledger(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"vault-ledger", mint.as_ref()], &ID)
+    }
+
+    /// Id allocator of a maker's intrachain offers.
+    pub fn trade_counter(maker: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"trade-counter", maker.as_ref()], &ID)
+    }
+
+    /// Id allocator of origin-SOL interchain offers.
+    pub fn global_trade_counter() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"trade-counter"], &ID)
+    }
+
+    /// Temporary account wSOL is unwrapped through for `owner`.
+    pub fn wsol_temp(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"wsol-temp", owner.as_ref()], &ID)
+    }
+
+    /// Hash time-locked offer, `seeds = [b"htlc", depositor, id]`.
+    pub fn htlc(depositor: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"htlc", depositor.as_ref(), &id.to_le_bytes()], &ID)
+    }
+
+    /// Used signed-order nonces of `maker` on the page holding `nonce`.
+    pub fn nonces(maker: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+        let page = nonce / swap::NONCES_PER_PAGE;
+        Pubkey::find_program_address(&[b"nonces", maker.as_ref(), &page.to_le_bytes()], &ID)
+    }
+
+    /// Relayer attestations of an origin-EVM trade.
+    pub fn attestation(external_seller_sol: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"attestation",
+                external_seller_sol.as_ref(),
+                &id.to_le_bytes(),
+            ],
+            &ID,
+        )
+    }
+
+    /// Global program configuration, `seeds = [b"config"]`.
+    pub fn config() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"config"], &ID)
+    }
+
+    /// Protocol treasury collecting fees in lamports and, through its
+    /// associated token accounts, in tokens.
+    pub fn treasury() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"treasury"], &ID)
+    }
+
+    /// Allowlist of relayers, `seeds = [b"relayer-registry"]`.
+    pub fn relayer_registry() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"relayer-registry"], &ID)
+    }
+}
+
+/// Instruction builders, one per instruction of `swap`, taking the program's
+/// own accounts and argument structs.
+pub mod ix {
+    use super::*;
+
+    fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        }
+    }
+
+    /// Appends remaining accounts, e.g. those a Token-2022 transfer hook or
+    /// `check_solvency` reads.
+    pub fn with_remaining_accounts(
+        mut instruction: Instruction,
+        accounts: impl IntoIterator<Item = AccountMeta>,
+    ) -> Instruction {
+        instruction.accounts.extend(accounts);
+        instruction
+    }
+
+    macro_rules! builders {
+        ($($name:ident($accounts:ident, $args:ident);)*) => {
+            $(
+                #[doc = concat!("Builds `", stringify!($name), "`.")]
+                pub fn $name(
+                    accounts: swap::accounts::$accounts,
+                    args: swap::instruction::$args,
+                ) -> Instruction {
+                    build(accounts, args)
+                }
+            )*
+        };
+    }
+
+    builders! {
+        deposit_seller_native(MakeOfferNative, DepositSellerNative);
+        deposit_seller_spl(MakeOfferSpl, DepositSellerSpl);
+        interchain_origin_sol_deposit_seller_native(InterchainOriginSolMakeOfferNative, InterchainOriginSolDepositSellerNative);
+        interchain_origin_evm_deposit_seller_native(InterchainMakeOfferNative, InterchainOriginEvmDepositSellerNative);
+        interchain_origin_sol_deposit_seller_spl(InterchainOriginSolMakeOfferSpl, InterchainOriginSolDepositSellerSpl);
+        interchain_origin_evm_deposit_seller_spl(InterchainMakeOfferSpl, InterchainOriginEvmDepositSellerSpl);
+        finalize_intrachain_offer(TakeOffer, FinalizeIntrachainOffer);
+        cancel_intrachain_offer(CancelOffer, CancelIntrachainOffer);
+        set_allowed_taker(SetAllowedTaker, SetAllowedTaker);
+        amend_offer(AmendOffer, AmendOffer);
+        fill_signed_order(FillSignedOrder, FillSignedOrder);
+        cancel_signed_order_nonce(CancelSignedOrderNonce, CancelSignedOrderNonce);
+        finalize_interchain_origin_evm_offer(TakeInterchainOffer, FinalizeInterchainOriginEvmOffer);
+        finalize_interchain_origin_sol_offer(TakeInterchainOriginSolOffer, FinalizeInterchainOriginSolOffer);
+        relay_offer_clone(RelayOfferClone, RelayOfferClone);
+        attest_evm_trade(AttestEvmTrade, AttestEvmTrade);
+        relay_signed_evm_trade(RelaySignedEvmTrade, RelaySignedEvmTrade);
+        refund_expired_offer(RefundExpiredOffer, RefundExpiredOffer);
+        refund_expired_interchain_offer(RefundExpiredInterchainOffer, RefundExpiredInterchainOffer);
+        refund_expired_interchain_origin_sol_offer(RefundExpiredInterchainOriginSolOffer, RefundExpiredInterchainOriginSolOffer);
+        bind_interchain_buyer(BindInterchainBuyer, BindInterchainBuyer);
+        reclaim_interchain_origin_sol_offer(ReclaimInterchainOriginSolOffer, ReclaimInterchainOriginSolOffer);
+        htlc_lock_native(HtlcLockNative, HtlcLockNative);
+        htlc_lock_spl(HtlcLockSpl, HtlcLockSpl);
+        htlc_claim(HtlcClaim, HtlcClaim);
+        htlc_refund(HtlcRefund, HtlcRefund);
+        initialize_config(InitializeConfig, InitializeConfig);
+        update_config(UpdateConfig, UpdateConfig);
+        propose_admin(UpdateConfig, ProposeAdmin);
+        accept_admin(AcceptAdmin, AcceptAdmin);
+        set_guardian(UpdateConfig, SetGuardian);
+        set_pause(SetPause, SetPause);
+        add_relayer(ManageRelayers, AddRelayer);
+        remove_relayer(ManageRelayers, RemoveRelayer);
+        set_relayer_threshold(ManageRelayers, SetRelayerThreshold);
+        add_evm_signer(ManageRelayers, AddEvmSigner);
+        remove_evm_signer(ManageRelayers, RemoveEvmSigner);
+        set_fees(UpdateConfig, SetFees);
+        withdraw_fees(WithdrawFees, WithdrawFees);
+        sweep_surplus(SweepSurplus, SweepSurplus);
+        refund_legacy_offer(RefundLegacyOffer, RefundLegacyOffer);
+        check_solvency(CheckSolvency, CheckSolvency);
+    }
+}
+
+/// Deserializers for account data as fetched from RPC, discriminator included.
+pub mod state {
+    use super::*;
+    use swap::{InterchainOffer, InterchainOriginSOlOffer, Offer};
+
+    pub fn offer(data: &[u8]) -> Result<Offer> {
+        Offer::try_deserialize(&mut &data[..])
+    }
+
+    pub fn interchain_offer(data: &[u8]) -> Result<InterchainOffer> {
+        InterchainOffer::try_deserialize(&mut &data[..])
+    }
+
+    pub fn interchain_origin_sol_offer(data: &[u8]) -> Result<InterchainOriginSOlOffer> {
+        InterchainOriginSOlOffer::try_deserialize(&mut &data[..])
+    }
+}
+
+/// Decoders for the events `swap` emits as `Program data:` log lines.
+pub mod events {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    macro_rules! swap_events {
+        ($($event:ident),* $(,)?) => {
+            /// Any event emitted by `swap`.
+            #[derive(Clone, Debug)]
+            pub enum SwapEvent {
+                $($event(swap::$event),)*
+            }
+
+            /// Decodes an event payload: the 8-byte discriminator followed by the
+            /// Borsh-encoded event. `None` if it is not a `swap` event.
+            pub fn decode(data: &[u8]) -> Option<SwapEvent> {
+                let (discriminator, mut body) = (data.get(..8)?, &data[8..]);
+                $(
+                    if discriminator == swap::$event::DISCRIMINATOR {
+                        return swap::$event::deserialize(&mut body)
+                            .ok()
+                            .map(SwapEvent::$event);
+                    }
+                )*
+                None
+            }
+        };
+    }
+
+    swap_events! {
+        CreateTradeEvent,
+        InterchainOriginSolCreateTradeEvent,
+        InterchainCreateTradeEvent,
+        InterchainSwapCompletedEvent,
+        PartialFillEvent,
+        SwapCompletedEvent,
+        OfferCancelledEvent,
+        OfferAmendedEvent,
+        AllowedTakerSetEvent,
+        ExpiredOfferRefundedEvent,
+        RelayEvmTradeEvent,
+        ConfigUpdatedEvent,
+        AdminProposedEvent,
+        AdminTransferredEvent,
+        GuardianUpdatedEvent,
+        PauseUpdatedEvent,
+        FeesUpdatedEvent,
+        FeesWithdrawnEvent,
+        RelayerAddedEvent,
+        RelayerRemovedEvent,
+        RelayerThresholdUpdatedEvent,
+        TradeAttestedEvent,
+        ConflictingAttestationEvent,
+        EvmSignerAddedEvent,
+        EvmSignerRemovedEvent,
+        SignedOrderFilledEvent,
+        SignedOrderNonceCancelledEvent,
+        InterchainBuyerBoundEvent,
+        InterchainOfferReclaimedEvent,
+        HtlcLockedEvent,
+        HtlcClaimedEvent,
+        HtlcRefundedEvent,
+        SolvencyReportEvent,
+        SurplusSweptEvent,
+        LegacyOfferRefundedEvent,
+    }
+
+    /// Decodes the events in a transaction's log messages. Only `Program data:`
+    /// lines logged while `swap` itself is executing are considered, so data
+    /// logged by programs it invokes is skipped.
+    pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<SwapEvent> {
+        let program_id = ID.to_string();
+        let mut invoked: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+
+        for log in logs {
+            let Some(rest) = log.as_ref().strip_prefix("Program ") else {
+                continue;
+            };
+            if let Some(data) = rest.strip_prefix("data: ") {
+                if invoked.last() == Some(&program_id.as_str()) {
+                    if let Some(event) = STANDARD.decode(data).ok().and_then(|data| decode(&data)) {
+                        events.push(event);
+                    }
+                }
+                continue;
+            }
+
+            let mut words = rest.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => invoked.push(program),
+                (Some(_), Some("success" | "failed:")) => {
+                    invoked.pop();
+                }
+                _ => {}
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::events::{decode, parse_logs, SwapEvent};
+    use super::*;
+    use anchor_lang::Event;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use swap::{OfferCancelledEvent, OfferStatus, PauseUpdatedEvent};
+
+    fn cancelled(id: u64) -> OfferCancelledEvent {
+        OfferCancelledEvent {
+            id,
+            maker: Pubkey::new_unique(),
+            token_a_refunded: 1_000,
+            is_native: false,
+            status: OfferStatus::Cancelled,
+        }
+    }
+
+    fn data_log(event: &impl Event) -> String {
+        format!("Program data: {}", STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn decode_round_trips_an_event() {
+        let event = cancelled(7);
+        let Some(SwapEvent::OfferCancelledEvent(decoded)) = decode(&event.data()) else {
+            panic!("not decoded as OfferCancelledEvent");
+        };
+        assert_eq!(decoded.id, event.id);
+        assert_eq!(decoded.maker, event.maker);
+        assert_eq!(decoded.token_a_refunded, event.token_a_refunded);
+        assert_eq!(decoded.status, OfferStatus::Cancelled);
+    }
+
+    #[test]
+    fn decode_rejects_foreign_and_short_payloads() {
+        assert!(decode(&[0; 16]).is_none());
+        assert!(decode(&[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn parse_logs_skips_data_logged_by_invoked_programs() {
+        let other = Pubkey::new_unique();
+        let pause = PauseUpdatedEvent {
+            authority: Pubkey::new_unique(),
+            paused: 1,
+        };
+        let logs = vec![
+            format!("Program {ID} invoke [1]"),
+            "Program log: Instruction: CancelOffer".to_string(),
+            format!("Program {other} invoke [2]"),
+            // Same bytes as a swap event, but logged by the invoked program.
+            data_log(&pause),
+            format!("Program {other} consumed 2000 of 190000 compute units"),
+            format!("Program {other} success"),
+            data_log(&cancelled(1)),
+            format!("Program {other} invoke [2]"),
+            format!("Program {other} failed: custom program error: 0x1"),
+            data_log(&cancelled(2)),
+            format!("Program {ID} consumed 20000 of 200000 compute units"),
+            format!("Program {ID} success"),
+            // Logged after swap returned, outside any swap frame.
+            data_log(&pause),
+        ];
+
+        let ids: Vec<u64> = parse_logs(&logs)
+            .into_iter()
+            .map(|event| match event {
+                SwapEvent::OfferCancelledEvent(event) => event.id,
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(ids, [1, 2]);
+    }
+}
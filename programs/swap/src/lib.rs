@@ -1,8 +1,24 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
 use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token::{self, spl_token::native_mint},
+    token_2022::{
+        self,
+        spl_token_2022::{
+            self,
+            extension::{
+                transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+                BaseStateWithExtensions, StateWithExtensions,
+            },
+        },
+    },
+    token_2022_extensions::transfer_fee::{
+        harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+    },
+    token_interface::{self, Mint, TokenAccount, TokenInterface},
 };
 
 //program_id
@@ -12,38 +28,49 @@ declare_id!("EPwQBZMG7y7VbijnPfPk7qgqR78TvZucSFP14zygkrAS");
 pub mod swap {
     use super::*;
     /// Intra-Chain
-    /// For depositing **raw SOL** into a System-owned vault.
+    /// For depositing **raw SOL**, wrapped into a wSOL escrow so SOL offers
+    /// settle through the same token path as SPL offers. A SOL leg is identified
+    /// by the wrapped SOL mint. Without an `id` the maker's trade counter
+    /// assigns one; the id used is returned.
     pub fn deposit_seller_native(
         ctx: Context<MakeOfferNative>,
-        id: u64,
+        id: Option<u64>,
         token_b_wanted_amount: u64,
         sol_offered_amount: u64,
-        is_taker_native: bool,
-    ) -> Result<()> {
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+    ) -> Result<u64> {
+        // Assigns the next id unless an explicit one is passed.
+        let id = ctx
+            .accounts
+            .trade_counter
+            .assign(id, ctx.bumps.trade_counter)?;
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTRACHAIN_DEPOSITS)?;
+
         require!(sol_offered_amount > 0, P2PError::InvalidAmount);
 
-        // Transfer SOL from maker to the system-owned vault.
-        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.maker.key(),
-            &ctx.accounts.vault.key(),
-            sol_offered_amount,
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
         );
 
-        anchor_lang::solana_program::program::invoke_signed(
-            &transfer_ix,
-            &[
-                ctx.accounts.maker.to_account_info(),
-                ctx.accounts.vault.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-            // &[&[b"vault-native", &[ctx.bumps.vault]]],
-            &[], // No signer seeds needed for maker’s signature
+        wrap_sol(
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            ctx.accounts.maker.to_account_info(),
+            ctx.accounts.escrow_spl.to_account_info(),
+            sol_offered_amount,
         )?;
         msg!(
-            "Intrachain Native SOL transfer completed. Moved {} lamports into vault.",
+            "Intrachain Native SOL transfer completed. Wrapped {} lamports into escrow.",
             sol_offered_amount
         );
 
+        let is_taker_native = ctx.accounts.token_mint_b.key() == native_mint::ID;
+
         // Populate Offer data
         ctx.accounts.offer.set_inner(Offer {
             id,
@@ -54,51 +81,89 @@ pub mod swap {
             token_b_wanted_amount,
             is_native: true,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
+            remaining_a: sol_offered_amount,
+            remaining_b: token_b_wanted_amount,
+            fee_collected: 0,
+            allowed_taker,
             bump: ctx.bumps.offer,
         });
 
+        ctx.accounts.vault_ledger.credit(
+            native_mint::ID,
+            ctx.bumps.vault_ledger,
+            sol_offered_amount,
+        )?;
+
         emit!(CreateTradeEvent {
             id,
             maker: ctx.accounts.maker.key(),
             token_a_offered_amount: sol_offered_amount,
             token_b_wanted_amount,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
         });
 
-        Ok(())
+        Ok(id)
     }
 
     /// Intra-Chain
-    /// For depositing **SPL tokens** into an ATA vault.
-    pub fn deposit_seller_spl(
-        ctx: Context<MakeOfferSpl>,
-        id: u64,
+    /// For depositing **SPL tokens**, including wSOL, into a per-offer escrow.
+    /// Without an `id` the maker's trade counter assigns one; the id used is
+    /// returned.
+    pub fn deposit_seller_spl<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeOfferSpl<'info>>,
+        id: Option<u64>,
         token_b_wanted_amount: u64,
         token_a_offered_amount: u64,
-        is_taker_native: bool,
-    ) -> Result<()> {
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+    ) -> Result<u64> {
+        // Assigns the next id unless an explicit one is passed.
+        let id = ctx
+            .accounts
+            .trade_counter
+            .assign(id, ctx.bumps.trade_counter)?;
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTRACHAIN_DEPOSITS)?;
+
         require!(token_a_offered_amount > 0, P2PError::InvalidAmount);
 
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
         // Transfer SPL tokens from the maker's token account to the vault (ATA owned by Offer).
         // We can use the anchor_spl::token::transfer CPI:
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.maker_token_account_a.to_account_info(),
-                    to: ctx.accounts.vault_spl.to_account_info(),
-                    authority: ctx.accounts.maker.to_account_info(),
-                },
-            ),
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.maker_token_account_a.to_account_info(),
+            &ctx.accounts.token_mint_a,
+            ctx.accounts.escrow_spl.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            ctx.remaining_accounts,
             token_a_offered_amount,
+            &[],
         )?;
+
+        // A Token-2022 transfer fee is withheld from the deposit, so the offer
+        // records what the escrow actually received.
+        ctx.accounts.escrow_spl.reload()?;
+        let token_a_offered_amount = ctx.accounts.escrow_spl.amount;
         msg!(
             "Intrachain SPL token transfer completed: {} tokens moved into vault.",
             token_a_offered_amount
         );
 
+        // Either leg may be wrapped SOL, which the payout paths unwrap on request.
+        let is_native = ctx.accounts.token_mint_a.key() == native_mint::ID;
+        let is_taker_native = ctx.accounts.token_mint_b.key() == native_mint::ID;
+
         // Populate Offer data
         ctx.accounts.offer.set_inner(Offer {
             id,
@@ -107,42 +172,72 @@ pub mod swap {
             token_mint_b: ctx.accounts.token_mint_b.key(),
             token_a_offered_amount,
             token_b_wanted_amount,
-            is_native: false,
+            is_native,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
+            remaining_a: token_a_offered_amount,
+            remaining_b: token_b_wanted_amount,
+            fee_collected: 0,
+            allowed_taker,
             bump: ctx.bumps.offer,
         });
 
+        ctx.accounts.vault_ledger.credit(
+            ctx.accounts.token_mint_a.key(),
+            ctx.bumps.vault_ledger,
+            token_a_offered_amount,
+        )?;
+
         emit!(CreateTradeEvent {
             id,
             maker: ctx.accounts.maker.key(),
             token_a_offered_amount,
             token_b_wanted_amount,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
         });
 
-        Ok(())
+        Ok(id)
     }
 
     /// Interchain => Origin is SOL chain
     /// For depositing **raw SOL** into a System-owned vault.
     /// ID: trade id, offered_amount, wanted_amount, is_taker_native,
+    /// Without an `id` the global trade counter assigns one; the id used is
+    /// returned.
     pub fn interchain_origin_sol_deposit_seller_native(
         ctx: Context<InterchainOriginSolMakeOfferNative>,
-        id: u64,
+        id: Option<u64>,
         // external_seller_sol: Pubkey,
         seller_evm: [u8; 20], // EVM address
         token_b_wanted_amount: u64,
         sol_offered_amount: u64,
         is_taker_native: bool,
-    ) -> Result<()> {
+        expires_at: i64,
+    ) -> Result<u64> {
+        // Assigns the next id unless an explicit one is passed.
+        let id = ctx
+            .accounts
+            .trade_counter
+            .assign(id, ctx.bumps.trade_counter)?;
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTERCHAIN_DEPOSITS)?;
+
         require!(sol_offered_amount > 0, P2PError::InvalidAmount);
 
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
         // Transfer SOL from maker to the system-owned vault.
         let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
             &ctx.accounts.seller_sol.key(),
-            &ctx.accounts.vault.key(),
+            &ctx.accounts.escrow_native.key(),
             sol_offered_amount,
         );
 
@@ -150,7 +245,7 @@ pub mod swap {
             &transfer_ix,
             &[
                 ctx.accounts.seller_sol.to_account_info(),
-                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.escrow_native.to_account_info(),
                 ctx.accounts.system_program.to_account_info(),
             ],
             // &[&[b"vault-native", &[ctx.bumps.vault]]],
@@ -172,10 +267,19 @@ pub mod swap {
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_seller_origin_sol = true;
         offer.is_taker_native = is_taker_native;
-        offer.is_swap_completed = false;
+        offer.status = OfferStatus::Funded;
         offer.is_native = true;
+        offer.token_mint_a = ctx.accounts.token_mint_a.key();
+        offer.expires_at = expires_at;
+        offer.created_at = Clock::get()?.unix_timestamp;
         offer.bump = ctx.bumps.interchain_origin_sol_offer;
 
+        ctx.accounts.vault_ledger.credit(
+            native_mint::ID,
+            ctx.bumps.vault_ledger,
+            sol_offered_amount,
+        )?;
+
         emit!(InterchainOriginSolCreateTradeEvent {
             id,
             seller_sol: ctx.accounts.seller_sol.key(),
@@ -184,28 +288,60 @@ pub mod swap {
             token_b_wanted_amount,
             is_taker_native,
             is_native: true,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
         });
 
-        Ok(())
+        Ok(id)
     }
 
     /// Interchain => Origin is EVM chain
+    #[allow(clippy::too_many_arguments)]
     pub fn interchain_origin_evm_deposit_seller_native(
         ctx: Context<InterchainMakeOfferNative>,
         id: u64,
-        external_seller_sol: Pubkey,
+        _external_seller_sol: Pubkey,
         buyer_evm: [u8; 20], // EVM address
         token_b_wanted_amount: u64,
         sol_offered_amount: u64,
         is_taker_native: bool,
+        expires_at: i64,
     ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTERCHAIN_DEPOSITS)?;
+
         require!(sol_offered_amount > 0, P2PError::InvalidAmount);
 
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
+        // The deposit must match the terms relayed from the EVM side.
+        let offer = &ctx.accounts.offer;
+        require!(
+            offer.status.can_transition_to(OfferStatus::Funded),
+            P2PError::InvalidStateTransition
+        );
+        require!(
+            Clock::get()?.unix_timestamp < offer.expires_at,
+            P2PError::OfferExpired
+        );
+        require!(
+            offer.is_taker_native && is_taker_native,
+            P2PError::DepositMismatch
+        );
+        require!(
+            token_b_wanted_amount == offer.token_b_wanted_amount
+                && sol_offered_amount == offer.token_b_wanted_amount,
+            P2PError::DepositMismatch
+        );
+
         // Transfer SOL from maker to the system-owned vault.
         let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
             &ctx.accounts.buyer_sol.key(),
-            &ctx.accounts.vault.key(),
+            &ctx.accounts.escrow_native.key(),
             sol_offered_amount,
         );
 
@@ -213,7 +349,7 @@ pub mod swap {
             &transfer_ix,
             &[
                 ctx.accounts.buyer_sol.to_account_info(),
-                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.escrow_native.to_account_info(),
                 ctx.accounts.system_program.to_account_info(),
             ],
             // &[&[b"vault-native", &[ctx.bumps.vault]]],
@@ -230,6 +366,17 @@ pub mod swap {
         let offer = &mut ctx.accounts.offer;
         offer.buyer_sol = ctx.accounts.buyer_sol.key();
         offer.buyer_evm = buyer_evm;
+        offer.expires_at = expires_at;
+        offer.is_native = true;
+        offer.status.transition(OfferStatus::Funded)?;
+        offer.deposited_amount = sol_offered_amount;
+        offer.deposited_mint = Pubkey::default();
+
+        ctx.accounts.vault_ledger.credit(
+            native_mint::ID,
+            ctx.bumps.vault_ledger,
+            sol_offered_amount,
+        )?;
 
         emit!(InterchainCreateTradeEvent {
             id,
@@ -237,36 +384,59 @@ pub mod swap {
             token_a_offered_amount: sol_offered_amount,
             token_b_wanted_amount,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
         });
 
         Ok(())
     }
 
     /// Interchain => Origin is SOl chain
-    pub fn interchain_origin_sol_deposit_seller_spl(
-        ctx: Context<InterchainOriginSolMakeOfferSpl>,
-        id: u64,
+    /// Without an `id` the global trade counter assigns one; the id used is
+    /// returned.
+    pub fn interchain_origin_sol_deposit_seller_spl<'info>(
+        ctx: Context<'_, '_, '_, 'info, InterchainOriginSolMakeOfferSpl<'info>>,
+        id: Option<u64>,
         seller_evm: [u8; 20], // EVM address
         token_b_wanted_amount: u64,
         token_a_offered_amount: u64,
         is_taker_native: bool,
-    ) -> Result<()> {
+        expires_at: i64,
+    ) -> Result<u64> {
+        // Assigns the next id unless an explicit one is passed.
+        let id = ctx
+            .accounts
+            .trade_counter
+            .assign(id, ctx.bumps.trade_counter)?;
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTERCHAIN_DEPOSITS)?;
+
         require!(token_a_offered_amount > 0, P2PError::InvalidAmount);
 
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
         // Transfer SPL tokens from the maker's token account to the vault (ATA owned by Offer).
         // We can use the anchor_spl::token::transfer CPI:
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.seller_sol_token_account_a.to_account_info(),
-                    to: ctx.accounts.vault_spl.to_account_info(),
-                    authority: ctx.accounts.seller_sol.to_account_info(),
-                },
-            ),
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.seller_sol_token_account_a.to_account_info(),
+            &ctx.accounts.token_mint_a,
+            ctx.accounts.escrow_spl.to_account_info(),
+            ctx.accounts.seller_sol.to_account_info(),
+            ctx.remaining_accounts,
             token_a_offered_amount,
+            &[],
         )?;
+
+        // A Token-2022 transfer fee is withheld from the deposit, so the offer
+        // records what the escrow actually received.
+        ctx.accounts.escrow_spl.reload()?;
+        let token_a_offered_amount = ctx.accounts.escrow_spl.amount;
         msg!(
             "Interchain SPL token transfer completed: {} tokens moved into vault.",
             token_a_offered_amount
@@ -283,48 +453,97 @@ pub mod swap {
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_seller_origin_sol = true;
         offer.is_taker_native = is_taker_native;
-        offer.is_swap_completed = false;
+        offer.status = OfferStatus::Funded;
         offer.is_native = false;
+        offer.token_mint_a = ctx.accounts.token_mint_a.key();
+        offer.expires_at = expires_at;
+        offer.created_at = Clock::get()?.unix_timestamp;
         offer.bump = ctx.bumps.interchain_origin_sol_offer;
 
+        ctx.accounts.vault_ledger.credit(
+            ctx.accounts.token_mint_a.key(),
+            ctx.bumps.vault_ledger,
+            token_a_offered_amount,
+        )?;
+
         emit!(InterchainOriginSolCreateTradeEvent {
             id,
             seller_sol: ctx.accounts.seller_sol.key(),
             seller_evm,
-            token_a_offered_amount: token_a_offered_amount,
+            token_a_offered_amount,
             token_b_wanted_amount,
             is_taker_native,
             is_native: true,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
         });
-        Ok(())
+        Ok(id)
     }
 
     /// Interchain => Origin is EVM chain
-    pub fn interchain_origin_evm_deposit_seller_spl(
-        ctx: Context<InterchainMakeOfferSpl>,
+    #[allow(clippy::too_many_arguments)]
+    pub fn interchain_origin_evm_deposit_seller_spl<'info>(
+        ctx: Context<'_, '_, '_, 'info, InterchainMakeOfferSpl<'info>>,
         id: u64,
-        external_seller_sol: Pubkey,
+        _external_seller_sol: Pubkey,
         buyer_evm: [u8; 20], // EVM address
         token_b_wanted_amount: u64,
         token_a_offered_amount: u64,
         is_taker_native: bool,
+        expires_at: i64,
     ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTERCHAIN_DEPOSITS)?;
+
         require!(token_a_offered_amount > 0, P2PError::InvalidAmount);
 
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
+        // The deposit must match the terms relayed from the EVM side.
+        let offer = &ctx.accounts.offer;
+        require!(
+            offer.status.can_transition_to(OfferStatus::Funded),
+            P2PError::InvalidStateTransition
+        );
+        require!(
+            Clock::get()?.unix_timestamp < offer.expires_at,
+            P2PError::OfferExpired
+        );
+        require!(
+            !offer.is_taker_native && !is_taker_native,
+            P2PError::DepositMismatch
+        );
+        require!(
+            ctx.accounts.token_mint_a.key() == offer.token_mint_a,
+            P2PError::InvalidTokenMint
+        );
+        require!(
+            token_b_wanted_amount == offer.token_b_wanted_amount
+                && token_a_offered_amount == offer.token_b_wanted_amount,
+            P2PError::DepositMismatch
+        );
+
         // Transfer SPL tokens from the maker's token account to the vault (ATA owned by Offer).
         // We can use the anchor_spl::token::transfer CPI:
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.buyer_sol_token_account_a.to_account_info(),
-                    to: ctx.accounts.vault_spl.to_account_info(),
-                    authority: ctx.accounts.buyer_sol.to_account_info(),
-                },
-            ),
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.buyer_sol_token_account_a.to_account_info(),
+            &ctx.accounts.token_mint_a,
+            ctx.accounts.escrow_spl.to_account_info(),
+            ctx.accounts.buyer_sol.to_account_info(),
+            ctx.remaining_accounts,
             token_a_offered_amount,
+            &[],
         )?;
+
+        // A Token-2022 transfer fee is withheld from the deposit, so the offer
+        // records what the escrow actually received.
+        ctx.accounts.escrow_spl.reload()?;
+        let token_a_offered_amount = ctx.accounts.escrow_spl.amount;
         msg!(
             "Interchain SPL token transfer completed: {} tokens moved into vault.",
             token_a_offered_amount
@@ -336,265 +555,882 @@ pub mod swap {
         let offer = &mut ctx.accounts.offer;
         offer.buyer_sol = ctx.accounts.buyer_sol.key();
         offer.buyer_evm = buyer_evm;
+        offer.expires_at = expires_at;
+        offer.is_native = false;
+        offer.status.transition(OfferStatus::Funded)?;
+        offer.deposited_amount = token_a_offered_amount;
+        offer.deposited_mint = ctx.accounts.token_mint_a.key();
+
+        ctx.accounts.vault_ledger.credit(
+            ctx.accounts.token_mint_a.key(),
+            ctx.bumps.vault_ledger,
+            token_a_offered_amount,
+        )?;
 
         emit!(InterchainCreateTradeEvent {
             id,
             buyer: ctx.accounts.buyer_sol.key(),
-            token_a_offered_amount: token_a_offered_amount,
+            token_a_offered_amount,
             token_b_wanted_amount,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Funded,
+            expires_at,
         });
 
         Ok(())
     }
 
-    pub fn finalize_intrachain_offer(ctx: Context<TakeOffer>, id: u64) -> Result<()> {
+    /// Intra-Chain
+    /// Fills `fill_amount` of the offered token A. The taker pays the pro-rata
+    /// share of token B (see `Offer::token_b_owed`) and the offer stays open
+    /// until the whole of token A has been taken.
+    pub fn finalize_intrachain_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, TakeOffer<'info>>,
+        _id: u64,
+        fill_amount: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.offer.key();
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_FINALIZATIONS)?;
+
         let offer = &mut ctx.accounts.offer;
 
-        // Ensure the offer has not already been filled.
-        require!(!offer.is_swap_completed, P2PError::SwapAlreadyCompleted);
+        // Stale offers cannot be filled at outdated prices.
+        require!(
+            Clock::get()?.unix_timestamp < offer.expires_at,
+            P2PError::OfferExpired
+        );
         // Prevent a maker from filling their own offer.
         require!(
             offer.maker != ctx.accounts.taker.key(),
             P2PError::MakerAndTakerCannotBeSame
         );
-
-        if offer.is_native {
-            let vault_bump = ctx.bumps.vault_native;
-            let seeds: &[&[u8]] = &[b"vault-native", &[vault_bump]];
-            let signer_seeds = &[&seeds[..]];
-
-            // -------------------------------
-            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-                &ctx.accounts.vault_native.key(),
-                &ctx.accounts.taker.key(),
-                offer.token_a_offered_amount,
+        // A private offer can only be filled by its designated taker.
+        if let Some(allowed_taker) = offer.allowed_taker {
+            require_keys_eq!(
+                allowed_taker,
+                ctx.accounts.taker.key(),
+                P2PError::UnauthorizedTaker
             );
+        }
+        require!(
+            fill_amount > 0 && fill_amount <= offer.remaining_a,
+            P2PError::InvalidFillAmount
+        );
 
-            anchor_lang::solana_program::program::invoke_signed(
-                &transfer_ix,
-                &[
-                    ctx.accounts.vault_native.to_account_info(),
-                    ctx.accounts.taker.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                signer_seeds,
-            )?;
+        let token_b_owed = offer.token_b_owed(fill_amount)?;
+        // The protocol fee is taken out of the token A released from escrow.
+        let fee = fee_amount(fill_amount, ctx.accounts.config.intrachain_fee_bps)?;
+        let taker_amount = fill_amount - fee;
 
-            msg!(
-                "Intrachain Native SOL transferred {} lamports from native vault to taker.",
-                offer.token_a_offered_amount
-            );
-        } else {
-            // Use the global authority PDA to sign for the vault.
-            let global_authority_seeds =
-                &[b"global-authority".as_ref(), &[ctx.bumps.global_authority]];
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts.vault_ledger.debit(fill_amount)?;
 
-            let signer_seeds = [&global_authority_seeds[..]];
+        // The escrow is its own authority.
+        let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[ctx.bumps.escrow_spl]];
+        let signer_seeds = [seeds];
 
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.vault_spl.to_account_info(),
-                        to: ctx.accounts.taker_token_account_a.to_account_info(),
-                        authority: ctx.accounts.global_authority.to_account_info(),
-                    },
+        // -------------------------------
+        // Step 1: Transfer token A from escrow to the taker. Wrapped SOL is
+        // unwrapped to the taker's lamports when no token account is passed.
+        match &ctx.accounts.taker_token_account_a {
+            Some(taker_token_account_a) => transfer_tokens(
+                &ctx.accounts.token_program,
+                ctx.accounts.escrow_spl.to_account_info(),
+                &ctx.accounts.token_mint_a,
+                taker_token_account_a.to_account_info(),
+                ctx.accounts.escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                taker_amount,
+                &signer_seeds,
+            )?,
+            None => {
+                require!(offer.is_native, P2PError::MissingTokenAccount);
+                let wsol_temp = ctx
+                    .accounts
+                    .wsol_temp
+                    .as_ref()
+                    .ok_or(P2PError::MissingTokenAccount)?;
+                let temp_bump = ctx.bumps.wsol_temp.ok_or(P2PError::MissingTokenAccount)?;
+                let taker_key = ctx.accounts.taker.key();
+                let temp_seeds: &[&[u8]] = &[b"wsol-temp", taker_key.as_ref(), &[temp_bump]];
+
+                unwrap_sol(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.system_program,
+                    &ctx.accounts.token_mint_a,
+                    ctx.accounts.escrow_spl.to_account_info(),
                     &signer_seeds,
-                ),
-                offer.token_a_offered_amount,
-            )?;
+                    wsol_temp,
+                    temp_seeds,
+                    ctx.accounts.taker.to_account_info(),
+                    taker_amount,
+                )?;
+            }
+        }
 
-            msg!(
-                "Intrachain SPL tokens transferred from vault to taker: {} tokens",
-                offer.token_a_offered_amount
-            );
+        if fee > 0 {
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                ctx.accounts.escrow_spl.to_account_info(),
+                &ctx.accounts.token_mint_a,
+                ctx.accounts.treasury_token_account_a.to_account_info(),
+                ctx.accounts.escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                fee,
+                &signer_seeds,
+            )?;
         }
 
-        // -------------------------------
-        // Step 2: Transfer Taker's asset to Maker.
+        msg!(
+            "Intrachain token A transferred from escrow to taker: {}",
+            taker_amount
+        );
 
-        if offer.is_taker_native {
-            // Transfer SOL from taker to maker.
-            // The taker is the signer for the transfer.
+        // -------------------------------
+        // Step 2: Transfer token B from taker to maker. A SOL leg is paid from
+        // the taker's wSOL account or lamports, and received as whichever of the
+        // two the maker's side was given as.
+        match (
+            &ctx.accounts.taker_token_account_b,
+            &ctx.accounts.maker_token_account_b,
+        ) {
+            (Some(taker_token_account_b), Some(maker_token_account_b)) => transfer_tokens(
+                &ctx.accounts.token_program_b,
+                taker_token_account_b.to_account_info(),
+                &ctx.accounts.token_mint_b,
+                maker_token_account_b.to_account_info(),
+                ctx.accounts.taker.to_account_info(),
+                ctx.remaining_accounts,
+                token_b_owed,
+                &[],
+            )?,
+            (None, Some(maker_token_account_b)) => {
+                require!(offer.is_taker_native, P2PError::MissingTokenAccount);
+                wrap_sol(
+                    &ctx.accounts.token_program_b,
+                    &ctx.accounts.system_program,
+                    ctx.accounts.taker.to_account_info(),
+                    maker_token_account_b.to_account_info(),
+                    token_b_owed,
+                )?;
+            }
+            (None, None) => {
+                require!(offer.is_taker_native, P2PError::MissingTokenAccount);
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.taker.to_account_info(),
+                            to: ctx.accounts.maker.to_account_info(),
+                        },
+                    ),
+                    token_b_owed,
+                )?;
+            }
+            // Unwrapping the taker's wSOL would need a second temporary account;
+            // the taker can pay from lamports instead.
+            (Some(_), None) => return err!(P2PError::MissingTokenAccount),
+        }
 
-            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-                &ctx.accounts.taker.key(),
-                &ctx.accounts.maker.key(),
-                offer.token_b_wanted_amount,
-            );
+        msg!(
+            "Intrachain token B transferred from taker to maker: {}",
+            token_b_owed
+        );
 
-            anchor_lang::solana_program::program::invoke_signed(
-                &transfer_ix,
-                &[
-                    ctx.accounts.taker.to_account_info(),
-                    ctx.accounts.maker.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                &[],
-            )?;
+        // What each side is credited after any Token-2022 transfer fee.
+        let token_a_received = net_of_transfer_fee(&ctx.accounts.token_mint_a, taker_amount)?;
+        let token_b_received = net_of_transfer_fee(&ctx.accounts.token_mint_b, token_b_owed)?;
 
-            msg!(
-                "Native SOL transferred {} lamports from taker to maker.",
-                offer.token_b_wanted_amount
-            );
+        offer.remaining_a -= fill_amount;
+        offer.remaining_b -= token_b_owed;
+        let next_status = if offer.remaining_a > 0 {
+            OfferStatus::PartiallyFilled
         } else {
-            token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.taker_token_account_b.to_account_info(),
-                        to: ctx.accounts.maker_token_account_b.to_account_info(),
-                        authority: ctx.accounts.taker.to_account_info(),
-                    },
-                ),
-                offer.token_b_wanted_amount,
-            )?;
+            OfferStatus::Completed
+        };
+        offer.status.transition(next_status)?;
+        offer.fee_collected = offer
+            .fee_collected
+            .checked_add(fee)
+            .ok_or(P2PError::MathOverflow)?;
+
+        emit!(PartialFillEvent {
+            id: offer.id,
+            maker: offer.maker,
+            taker: ctx.accounts.taker.key(),
+            token_a_filled: fill_amount,
+            token_b_paid: token_b_owed,
+            token_a_received,
+            token_b_received,
+            fee,
+            remaining_a: offer.remaining_a,
+            remaining_b: offer.remaining_b,
+            status: offer.status,
+        });
 
-            msg!(
-                "SPL tokens transferred from taker to maker: {} tokens",
-                offer.token_b_wanted_amount
-            );
+        if offer.remaining_a > 0 {
+            return Ok(());
         }
 
-        // if you don't want on-chain state tracking, you can remove this line and just emit the event and close the offer account.
-        offer.is_swap_completed = true;
-
         emit!(SwapCompletedEvent {
             id: offer.id,
             maker: offer.maker,
             taker: ctx.accounts.taker.key(),
             token_a_transferred: offer.token_a_offered_amount,
             token_b_transferred: offer.token_b_wanted_amount,
-            is_swap_completed: true,
+            fee_collected: offer.fee_collected,
+            status: offer.status,
         });
 
+        // Only a fully filled offer is closed, together with its escrow, returning
+        // their rent to the maker.
+        close_token_escrow(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint_a,
+            &ctx.accounts.escrow_spl,
+            &ctx.accounts.maker.to_account_info(),
+            &signer_seeds,
+        )?;
+        ctx.accounts
+            .offer
+            .close(ctx.accounts.maker.to_account_info())?;
+
         Ok(())
     }
 
-    pub fn finalize_interchain_origin_evm_offer(
-        ctx: Context<TakeInterchainOffer>,
-        id: u64,
+    /// Intra-Chain
+    /// Lets the maker withdraw an open offer: the escrowed tokens are returned to
+    /// the maker, wSOL as lamports when no token account is passed, and the offer
+    /// account is closed back to them.
+    pub fn cancel_intrachain_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelOffer<'info>>,
+        _id: u64,
     ) -> Result<()> {
-        let offer = &mut ctx.accounts.offer;
-
-        // Ensure the offer has not already been filled.
-        require!(!offer.is_swap_completed, P2PError::SwapAlreadyCompleted);
-        // Prevent a maker from filling their own offer.
-        require!(
-            offer.buyer_sol != ctx.accounts.external_seller_sol.key(),
-            P2PError::MakerAndTakerCannotBeSame
-        );
+        let offer_key = ctx.accounts.offer.key();
 
-        msg!(
-            "Finalizing inter-chain swap: {} USDT from vault to seller {}",
-            offer.token_b_wanted_amount,
-            ctx.accounts.external_seller_sol.key()
-        );
-
-        // -------------------------------
-        // Step 1: Transfer Buyer's asset from vault to seller. for origin is EVM chain
-        // step 1: transfer seller's asset from vault to buyer for origin is SOL chain
-
-        if offer.is_native {
-            let vault_bump = ctx.bumps.vault_native;
-            let seeds: &[&[u8]] = &[b"vault-native", &[vault_bump]];
-            let signer_seeds = &[&seeds[..]];
-
-            // -------------------------------
-            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-                &ctx.accounts.vault_native.key(),
-                &ctx.accounts.external_seller_sol.key(),
-                offer.token_b_wanted_amount,
-            );
-
-            anchor_lang::solana_program::program::invoke_signed(
-                &transfer_ix,
-                &[
-                    ctx.accounts.vault_native.to_account_info(),
-                    ctx.accounts.external_seller_sol.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                signer_seeds,
-            )?;
-
-            msg!(
-                "Interchain Native SOL transferred {} lamports from native vault to seller.",
-                offer.token_b_wanted_amount
-            );
-        } else {
-            // Use the global authority PDA to sign for the vault.
-            let global_authority_seeds =
-                &[b"global-authority".as_ref(), &[ctx.bumps.global_authority]];
+        let offer = &mut ctx.accounts.offer;
 
-            let signer_seeds = [&global_authority_seeds[..]];
+        // A completed offer has nothing left in escrow.
+        offer.status.transition(OfferStatus::Cancelled)?;
+
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts.vault_ledger.debit(offer.remaining_a)?;
+
+        // The escrow is its own authority.
+        let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[ctx.bumps.escrow_spl]];
+        let signer_seeds = [seeds];
+
+        match &ctx.accounts.maker_token_account_a {
+            Some(maker_token_account_a) => {
+                transfer_tokens(
+                    &ctx.accounts.token_program,
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    &ctx.accounts.token_mint_a,
+                    maker_token_account_a.to_account_info(),
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    ctx.remaining_accounts,
+                    offer.remaining_a,
+                    &signer_seeds,
+                )?;
 
-            token::transfer(
-                CpiContext::new_with_signer(
+                close_token_escrow(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.token_mint_a,
+                    &ctx.accounts.escrow_spl,
+                    &ctx.accounts.maker.to_account_info(),
+                    &signer_seeds,
+                )?;
+            }
+            // Closing a wSOL escrow unwraps all of it, rent included, to the maker.
+            None => {
+                require!(offer.is_native, P2PError::MissingTokenAccount);
+                token_interface::close_account(CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.vault_spl.to_account_info(),
-                        to: ctx
-                            .accounts
-                            .external_seller_sol_token_account_a
-                            .to_account_info(),
-                        authority: ctx.accounts.global_authority.to_account_info(),
+                    token_interface::CloseAccount {
+                        account: ctx.accounts.escrow_spl.to_account_info(),
+                        destination: ctx.accounts.maker.to_account_info(),
+                        authority: ctx.accounts.escrow_spl.to_account_info(),
                     },
                     &signer_seeds,
-                ),
-                offer.token_b_wanted_amount,
-            )?;
-
-            msg!(
-                "Interchain SPL tokens transferred from vault to taker: {} tokens",
-                offer.token_a_offered_amount
-            );
+                ))?;
+            }
         }
 
-        // if you don't want on-chain state tracking, you can remove this line and just emit the event and close the offer account.
-        offer.is_swap_completed = true;
-
-        // emit logs
         msg!(
-            "Interchain offer completed for trade id: {},buyer : {} , external_seller_sol: {},
-        external_seller_evm : {:?}, is_swap_completed: {}, token_a_offered_amount: {},token_b_wanted_amount: {}, is_taker_native: {}",
-            offer.trade_id,
-            offer.buyer_sol,
-            offer.external_seller_sol,
-            offer.external_seller_evm,
-            offer.is_swap_completed,
-            offer.token_a_offered_amount,
-            offer.token_b_wanted_amount,
-            offer.is_taker_native
+            "Intrachain offer refunded from escrow to maker: {}",
+            offer.remaining_a
         );
 
-        emit!(InterchainSwapCompletedEvent {
-            id: offer.trade_id,
-            buyer: ctx.accounts.buyer_sol.key(),
-            seller: ctx.accounts.external_seller_sol.key(),
-            token_a_transferred: offer.token_a_offered_amount,
-            token_b_transferred: offer.token_b_wanted_amount,
-            is_swap_completed: true,
+        let token_a_refunded = net_of_transfer_fee(&ctx.accounts.token_mint_a, offer.remaining_a)?;
+
+        emit!(OfferCancelledEvent {
+            id: offer.id,
+            maker: offer.maker,
+            token_a_refunded,
+            is_native: offer.is_native,
+            status: offer.status,
         });
 
         Ok(())
     }
 
-    pub fn finalize_interchain_origin_sol_offer(
-        ctx: Context<TakeInterchainOriginSolOffer>,
-        id: u64,
+    /// Intra-Chain
+    /// Lets the maker assign, reassign or clear (`None`) the only taker allowed
+    /// to fill an open offer.
+    pub fn set_allowed_taker(
+        ctx: Context<SetAllowedTaker>,
+        _id: u64,
+        allowed_taker: Option<Pubkey>,
     ) -> Result<()> {
-        let offer = &mut ctx.accounts.interchain_origin_sol_offer;
+        let offer = &mut ctx.accounts.offer;
 
-        // Ensure the offer has not already been filled.
-        require!(!offer.is_swap_completed, P2PError::SwapAlreadyCompleted);
+        require!(offer.status.is_open(), P2PError::InvalidStateTransition);
+        require!(
+            allowed_taker != Some(offer.maker),
+            P2PError::MakerAndTakerCannotBeSame
+        );
+
+        offer.allowed_taker = allowed_taker;
+
+        emit!(AllowedTakerSetEvent {
+            id: offer.id,
+            maker: offer.maker,
+            allowed_taker,
+        });
+
+        Ok(())
+    }
+
+    /// Intra-Chain
+    /// Lets the maker re-terms an open offer: `remaining_a` is the new amount of
+    /// token A left in escrow (the difference is topped up from, or withdrawn to,
+    /// the maker), `remaining_b` the token B now wanted for it, and `expires_at`
+    /// may only be pushed back, and only before the offer has expired. A top-up of wSOL can come from the maker's
+    /// lamports and a withdrawal be unwrapped to them when no token account is
+    /// passed.
+    pub fn amend_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AmendOffer<'info>>,
+        _id: u64,
+        remaining_a: u64,
+        remaining_b: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.offer.key();
+
+        let offer = &mut ctx.accounts.offer;
+
+        require!(offer.status.is_open(), P2PError::InvalidStateTransition);
+        // An expired offer is only refunded, never revived.
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < offer.expires_at, P2PError::OfferExpired);
+        // Token A left in escrow must still cost something.
+        require!(remaining_a > 0 && remaining_b > 0, P2PError::InvalidAmount);
+        require!(
+            expires_at >= offer.expires_at && expires_at > now,
+            P2PError::InvalidExpiry
+        );
+
+        let old_remaining_a = offer.remaining_a;
+        let old_remaining_b = offer.remaining_b;
+        let old_expires_at = offer.expires_at;
+
+        if remaining_a > old_remaining_a {
+            // A top-up is a deposit.
+            ctx.accounts
+                .config
+                .require_not_paused(PAUSE_INTRACHAIN_DEPOSITS)?;
+
+            let top_up = remaining_a - old_remaining_a;
+            let escrowed_before = ctx.accounts.escrow_spl.amount;
+
+            match &ctx.accounts.maker_token_account_a {
+                Some(maker_token_account_a) => transfer_tokens(
+                    &ctx.accounts.token_program,
+                    maker_token_account_a.to_account_info(),
+                    &ctx.accounts.token_mint_a,
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    ctx.accounts.maker.to_account_info(),
+                    ctx.remaining_accounts,
+                    top_up,
+                    &[],
+                )?,
+                None => {
+                    require!(offer.is_native, P2PError::MissingTokenAccount);
+                    wrap_sol(
+                        &ctx.accounts.token_program,
+                        &ctx.accounts.system_program,
+                        ctx.accounts.maker.to_account_info(),
+                        ctx.accounts.escrow_spl.to_account_info(),
+                        top_up,
+                    )?;
+                }
+            }
+
+            // Only what the escrow received after any Token-2022 transfer fee is
+            // added to the offer.
+            ctx.accounts.escrow_spl.reload()?;
+            let received = ctx
+                .accounts
+                .escrow_spl
+                .amount
+                .checked_sub(escrowed_before)
+                .ok_or(P2PError::MathOverflow)?;
+
+            let ledger = &mut ctx.accounts.vault_ledger;
+            let (mint, bump) = (ledger.mint, ledger.bump);
+            ledger.credit(mint, bump, received)?;
+
+            offer.remaining_a = offer
+                .remaining_a
+                .checked_add(received)
+                .ok_or(P2PError::MathOverflow)?;
+            offer.token_a_offered_amount = offer
+                .token_a_offered_amount
+                .checked_add(received)
+                .ok_or(P2PError::MathOverflow)?;
+        } else if remaining_a < old_remaining_a {
+            let withdrawal = old_remaining_a - remaining_a;
+
+            // Refuses to pay out more than the mint's escrow is known to owe.
+            ctx.accounts.vault_ledger.debit(withdrawal)?;
+
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[ctx.bumps.escrow_spl]];
+            let signer_seeds = [seeds];
+
+            match &ctx.accounts.maker_token_account_a {
+                Some(maker_token_account_a) => transfer_tokens(
+                    &ctx.accounts.token_program,
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    &ctx.accounts.token_mint_a,
+                    maker_token_account_a.to_account_info(),
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    ctx.remaining_accounts,
+                    withdrawal,
+                    &signer_seeds,
+                )?,
+                None => {
+                    require!(offer.is_native, P2PError::MissingTokenAccount);
+                    let wsol_temp = ctx
+                        .accounts
+                        .wsol_temp
+                        .as_ref()
+                        .ok_or(P2PError::MissingTokenAccount)?;
+                    let temp_bump = ctx.bumps.wsol_temp.ok_or(P2PError::MissingTokenAccount)?;
+                    let maker_key = ctx.accounts.maker.key();
+                    let temp_seeds: &[&[u8]] = &[b"wsol-temp", maker_key.as_ref(), &[temp_bump]];
+
+                    unwrap_sol(
+                        &ctx.accounts.token_program,
+                        &ctx.accounts.system_program,
+                        &ctx.accounts.token_mint_a,
+                        ctx.accounts.escrow_spl.to_account_info(),
+                        &signer_seeds,
+                        wsol_temp,
+                        temp_seeds,
+                        ctx.accounts.maker.to_account_info(),
+                        withdrawal,
+                    )?;
+                }
+            }
+
+            offer.remaining_a = remaining_a;
+            offer.token_a_offered_amount = offer
+                .token_a_offered_amount
+                .checked_sub(withdrawal)
+                .ok_or(P2PError::MathOverflow)?;
+        }
+
+        // Token B already paid by earlier fills stays part of the total.
+        let token_b_paid = offer
+            .token_b_wanted_amount
+            .checked_sub(old_remaining_b)
+            .ok_or(P2PError::MathOverflow)?;
+        offer.remaining_b = remaining_b;
+        offer.token_b_wanted_amount = token_b_paid
+            .checked_add(remaining_b)
+            .ok_or(P2PError::MathOverflow)?;
+        offer.expires_at = expires_at;
+
+        emit!(OfferAmendedEvent {
+            id: offer.id,
+            maker: offer.maker,
+            old_remaining_a,
+            new_remaining_a: offer.remaining_a,
+            old_remaining_b,
+            new_remaining_b: offer.remaining_b,
+            old_expires_at,
+            new_expires_at: offer.expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Intra-Chain
+    /// Fills an off-chain order the maker signed with ed25519. The maker keeps a
+    /// standing token delegation to `global-authority`, so token A moves straight
+    /// from the maker's account; nothing is escrowed beforehand. The Ed25519
+    /// instruction verifying the signature must come right before this one.
+    pub fn fill_signed_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, FillSignedOrder<'info>>,
+        order: SignedOrder,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_FINALIZATIONS)?;
+
+        require!(
+            order.token_a_amount > 0 && order.token_b_amount > 0,
+            P2PError::InvalidAmount
+        );
+        require!(
+            Clock::get()?.unix_timestamp < order.expires_at,
+            P2PError::OfferExpired
+        );
+        require!(
+            order.maker != ctx.accounts.taker.key(),
+            P2PError::MakerAndTakerCannotBeSame
+        );
+
+        verify_ed25519_instruction(
+            &ctx.accounts.instructions_sysvar,
+            &order.maker,
+            &order.message(&crate::ID)?,
+        )?;
+
+        let nonce_bitmap = &mut ctx.accounts.nonce_bitmap;
+        nonce_bitmap.maker = order.maker;
+        nonce_bitmap.page = order.nonce / NONCES_PER_PAGE;
+        nonce_bitmap.bump = ctx.bumps.nonce_bitmap;
+        nonce_bitmap.consume(order.nonce)?;
+
+        let fee = fee_amount(order.token_a_amount, ctx.accounts.config.intrachain_fee_bps)?;
+        let taker_amount = order.token_a_amount - fee;
+
+        // -------------------------------
+        // Step 1: Transfer Maker's token A to the taker as the delegate.
+
+        let global_authority_seeds = &[b"global-authority".as_ref(), &[ctx.bumps.global_authority]];
+        let signer_seeds = [&global_authority_seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.maker_token_account_a.to_account_info(),
+            &ctx.accounts.token_mint_a,
+            ctx.accounts.taker_token_account_a.to_account_info(),
+            ctx.accounts.global_authority.to_account_info(),
+            ctx.remaining_accounts,
+            taker_amount,
+            &signer_seeds,
+        )?;
+
+        if fee > 0 {
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                ctx.accounts.maker_token_account_a.to_account_info(),
+                &ctx.accounts.token_mint_a,
+                ctx.accounts.treasury_token_account_a.to_account_info(),
+                ctx.accounts.global_authority.to_account_info(),
+                ctx.remaining_accounts,
+                fee,
+                &signer_seeds,
+            )?;
+        }
+
+        // -------------------------------
+        // Step 2: Transfer Taker's token B to the maker.
+
+        transfer_tokens(
+            &ctx.accounts.token_program_b,
+            ctx.accounts.taker_token_account_b.to_account_info(),
+            &ctx.accounts.token_mint_b,
+            ctx.accounts.maker_token_account_b.to_account_info(),
+            ctx.accounts.taker.to_account_info(),
+            ctx.remaining_accounts,
+            order.token_b_amount,
+            &[],
+        )?;
+
+        msg!(
+            "Signed order {} filled: {} token A to taker, {} token B to maker",
+            order.nonce,
+            taker_amount,
+            order.token_b_amount
+        );
+
+        let token_a_received = net_of_transfer_fee(&ctx.accounts.token_mint_a, taker_amount)?;
+        let token_b_received =
+            net_of_transfer_fee(&ctx.accounts.token_mint_b, order.token_b_amount)?;
+
+        emit!(SignedOrderFilledEvent {
+            maker: order.maker,
+            taker: ctx.accounts.taker.key(),
+            nonce: order.nonce,
+            token_mint_a: order.token_mint_a,
+            token_mint_b: order.token_mint_b,
+            token_a_amount: order.token_a_amount,
+            token_b_amount: order.token_b_amount,
+            token_a_received,
+            token_b_received,
+            fee_collected: fee,
+        });
+
+        Ok(())
+    }
+
+    /// Intra-Chain
+    /// Invalidates a signed order before anyone fills it.
+    pub fn cancel_signed_order_nonce(
+        ctx: Context<CancelSignedOrderNonce>,
+        nonce: u64,
+    ) -> Result<()> {
+        let nonce_bitmap = &mut ctx.accounts.nonce_bitmap;
+        nonce_bitmap.maker = ctx.accounts.maker.key();
+        nonce_bitmap.page = nonce / NONCES_PER_PAGE;
+        nonce_bitmap.bump = ctx.bumps.nonce_bitmap;
+        nonce_bitmap.consume(nonce)?;
+
+        emit!(SignedOrderNonceCancelledEvent {
+            maker: ctx.accounts.maker.key(),
+            nonce,
+        });
+
+        Ok(())
+    }
+
+    pub fn finalize_interchain_origin_evm_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, TakeInterchainOffer<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.offer.key();
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_FINALIZATIONS)?;
+
+        let offer = &mut ctx.accounts.offer;
+
+        // Only a funded offer can be filled, and only once.
+        offer.status.transition(OfferStatus::Completed)?;
+        // The EVM-side deposit must be confirmed by enough relayers.
+        require!(
+            offer.attestation_count >= offer.required_attestations,
+            P2PError::QuorumNotReached
+        );
+        // Stale offers cannot be filled at outdated prices.
+        require!(
+            Clock::get()?.unix_timestamp < offer.expires_at,
+            P2PError::OfferExpired
+        );
         // Prevent a maker from filling their own offer.
         require!(
-            offer.external_buyer_sol != ctx.accounts.seller_sol.key(),
+            offer.buyer_sol != ctx.accounts.external_seller_sol.key(),
             P2PError::MakerAndTakerCannotBeSame
         );
 
+        // The protocol fee is taken out of the amount released from escrow.
+        let fee = fee_amount(
+            offer.deposited_amount,
+            ctx.accounts.config.interchain_origin_evm_fee_bps,
+        )?;
+        let seller_amount = offer.deposited_amount - fee;
+
+        msg!(
+            "Finalizing inter-chain swap: {} USDT from vault to seller {}",
+            offer.deposited_amount,
+            ctx.accounts.external_seller_sol.key()
+        );
+
+        // -------------------------------
+        // Step 1: Transfer Buyer's asset from vault to seller. for origin is EVM chain
+        // step 1: transfer seller's asset from vault to buyer for origin is SOL chain
+
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts.vault_ledger.debit(offer.deposited_amount)?;
+
+        if offer.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+
+            // -------------------------------
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &escrow_native.key(),
+                &ctx.accounts.external_seller_sol.key(),
+                seller_amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    escrow_native.to_account_info(),
+                    ctx.accounts.external_seller_sol.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+
+            if fee > 0 {
+                let fee_ix = anchor_lang::solana_program::system_instruction::transfer(
+                    &escrow_native.key(),
+                    &ctx.accounts.treasury.key(),
+                    fee,
+                );
+
+                anchor_lang::solana_program::program::invoke_signed(
+                    &fee_ix,
+                    &[
+                        escrow_native.to_account_info(),
+                        ctx.accounts.treasury.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                    signer_seeds,
+                )?;
+            }
+
+            msg!(
+                "Interchain Native SOL transferred {} lamports from native vault to seller.",
+                seller_amount
+            );
+
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &ctx.accounts.buyer_sol.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                &ctx.accounts.token_mint_a,
+                ctx.accounts
+                    .external_seller_sol_token_account_a
+                    .to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                seller_amount,
+                &signer_seeds,
+            )?;
+
+            if fee > 0 {
+                transfer_tokens(
+                    &ctx.accounts.token_program,
+                    escrow_spl.to_account_info(),
+                    &ctx.accounts.token_mint_a,
+                    ctx.accounts.treasury_token_account_a.to_account_info(),
+                    escrow_spl.to_account_info(),
+                    ctx.remaining_accounts,
+                    fee,
+                    &signer_seeds,
+                )?;
+            }
+
+            msg!(
+                "Interchain SPL tokens transferred from vault to taker: {} tokens",
+                seller_amount
+            );
+
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint_a,
+                escrow_spl,
+                &ctx.accounts.buyer_sol.to_account_info(),
+                &signer_seeds,
+            )?;
+        }
+
+        offer.fee_collected = fee;
+
+        // emit logs
+        msg!(
+            "Interchain offer completed for trade id: {},buyer : {} , external_seller_sol: {},
+        external_seller_evm : {:?}, status: {:?}, token_a_offered_amount: {},token_b_wanted_amount: {}, is_taker_native: {}",
+            offer.trade_id,
+            offer.buyer_sol,
+            offer.external_seller_sol,
+            offer.external_seller_evm,
+            offer.status,
+            offer.token_a_offered_amount,
+            offer.token_b_wanted_amount,
+            offer.is_taker_native
+        );
+
+        let amount_received = if offer.is_native {
+            seller_amount
+        } else {
+            net_of_transfer_fee(&ctx.accounts.token_mint_a, seller_amount)?
+        };
+
+        emit!(InterchainSwapCompletedEvent {
+            id: offer.trade_id,
+            buyer: ctx.accounts.buyer_sol.key(),
+            seller: ctx.accounts.external_seller_sol.key(),
+            token_a_transferred: offer.token_a_offered_amount,
+            token_b_transferred: offer.deposited_amount,
+            fee_collected: fee,
+            amount_received,
+            status: offer.status,
+        });
+
+        Ok(())
+    }
+
+    pub fn finalize_interchain_origin_sol_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, TakeInterchainOriginSolOffer<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.interchain_origin_sol_offer.key();
+
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_FINALIZATIONS)?;
+
+        let offer = &mut ctx.accounts.interchain_origin_sol_offer;
+
+        // Only a funded offer can be filled, and only once.
+        offer.status.transition(OfferStatus::Completed)?;
+        // Stale offers cannot be filled at outdated prices.
+        require!(
+            Clock::get()?.unix_timestamp < offer.expires_at,
+            P2PError::OfferExpired
+        );
+        // Only the buyer recorded by `bind_interchain_buyer` can be paid.
+        require!(
+            offer.external_buyer_sol != Pubkey::default(),
+            P2PError::BuyerNotBound
+        );
+        require!(
+            offer.external_buyer_sol == ctx.accounts.external_buyer_sol.key(),
+            P2PError::InvalidBuyer
+        );
+
+        // The protocol fee is taken out of the amount released from escrow.
+        let fee = fee_amount(
+            offer.token_a_offered_amount,
+            ctx.accounts.config.interchain_origin_sol_fee_bps,
+        )?;
+        let buyer_amount = offer.token_a_offered_amount - fee;
+
         msg!(
             "Finalizing inter-chain swap: {} USDT from vault to buyer {}",
             offer.token_a_offered_amount,
@@ -605,77 +1441,152 @@ pub mod swap {
         // Step 1: Transfer Buyer's asset from vault to seller. for origin is EVM chain
         // step 1: transfer seller's asset from vault to buyer for origin is SOL chain => external_seller_sol = buyer_sol and external_seller_sol_token_account_a = buyer_sol_token_account_a
 
-        if offer.is_native {
-            let vault_bump = ctx.bumps.vault_native;
-            let seeds: &[&[u8]] = &[b"vault-native", &[vault_bump]];
-            let signer_seeds = &[&seeds[..]];
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts
+            .vault_ledger
+            .debit(offer.token_a_offered_amount)?;
+
+        let amount_received = if offer.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
 
             // -------------------------------
             let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-                &ctx.accounts.vault_native.key(),
+                &escrow_native.key(),
                 &ctx.accounts.external_buyer_sol.key(), //external_seller_sol = buyer_sol
-                offer.token_a_offered_amount,
+                buyer_amount,
             );
 
             anchor_lang::solana_program::program::invoke_signed(
                 &transfer_ix,
                 &[
-                    ctx.accounts.vault_native.to_account_info(),
+                    escrow_native.to_account_info(),
                     ctx.accounts.external_buyer_sol.to_account_info(), //external_seller_sol = buyer_sol
                     ctx.accounts.system_program.to_account_info(),
                 ],
                 signer_seeds,
             )?;
 
+            if fee > 0 {
+                let fee_ix = anchor_lang::solana_program::system_instruction::transfer(
+                    &escrow_native.key(),
+                    &ctx.accounts.treasury.key(),
+                    fee,
+                );
+
+                anchor_lang::solana_program::program::invoke_signed(
+                    &fee_ix,
+                    &[
+                        escrow_native.to_account_info(),
+                        ctx.accounts.treasury.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                    signer_seeds,
+                )?;
+            }
+
             msg!(
                 "Interchain Native SOL transferred {} lamports from native vault to buyer : {}.",
-                offer.token_a_offered_amount,
+                buyer_amount,
                 offer.external_buyer_sol
             );
-        } else {
-            // Use the global authority PDA to sign for the vault.
-            let global_authority_seeds =
-                &[b"global-authority".as_ref(), &[ctx.bumps.global_authority]];
 
-            let signer_seeds = [&global_authority_seeds[..]];
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &ctx.accounts.seller_sol.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
 
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.vault_spl.to_account_info(),
-                        to: ctx
-                            .accounts
-                            .external_buyer_sol_token_account_a //external_seller_sol_token_account_a = buyer_sol_token_account_a
-                            .to_account_info(),
-                        authority: ctx.accounts.global_authority.to_account_info(),
-                    },
-                    &signer_seeds,
-                ),
-                offer.token_a_offered_amount,
+            buyer_amount
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            let token_mint_a = ctx
+                .accounts
+                .token_mint_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenMint)?;
+            let buyer_token_account = ctx
+                .accounts
+                .external_buyer_sol_token_account_a //external_seller_sol_token_account_a = buyer_sol_token_account_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+            let treasury_token_account = ctx
+                .accounts
+                .treasury_token_account_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                token_mint_a,
+                buyer_token_account.to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                buyer_amount,
+                &signer_seeds,
             )?;
 
+            if fee > 0 {
+                transfer_tokens(
+                    &ctx.accounts.token_program,
+                    escrow_spl.to_account_info(),
+                    token_mint_a,
+                    treasury_token_account.to_account_info(),
+                    escrow_spl.to_account_info(),
+                    ctx.remaining_accounts,
+                    fee,
+                    &signer_seeds,
+                )?;
+            }
+
             msg!(
                 "Interchain SPL tokens : {} transferred from vault to taker: {} tokens",
-                offer.token_a_offered_amount,
+                buyer_amount,
                 ctx.accounts.external_buyer_sol.key()
             );
-        }
 
-        // if you don't want on-chain state tracking, you can remove this line and just emit the event and close the offer account.
-        offer.is_swap_completed = true;
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                token_mint_a,
+                escrow_spl,
+                &ctx.accounts.seller_sol.to_account_info(),
+                &signer_seeds,
+            )?;
+
+            net_of_transfer_fee(token_mint_a, buyer_amount)?
+        };
+
+        offer.fee_collected = fee;
 
         // emit logs
         // TODO : need to update this and event
         msg!(
             "Interchain origin sol offer completed for trade id: {},buyer : {} , seller_sol: {},
-        seller_evm : {:?}, to buyer's sol address : {}, is_swap_completed: {}, token_a_offered_amount: {},token_b_wanted_amount: {}, is_taker_native: {}",
+        seller_evm : {:?}, to buyer's sol address : {}, status: {:?}, token_a_offered_amount: {},token_b_wanted_amount: {}, is_taker_native: {}",
             offer.trade_id,
             offer.external_buyer_sol,
             offer.seller_sol,
             offer.seller_evm,
             offer.external_buyer_sol,
-            offer.is_swap_completed,
+            offer.status,
             offer.token_a_offered_amount,
             offer.token_b_wanted_amount,
             offer.is_taker_native
@@ -687,7 +1598,9 @@ pub mod swap {
             seller: ctx.accounts.seller_sol.key(),
             token_a_transferred: offer.token_a_offered_amount,
             token_b_transferred: offer.token_b_wanted_amount,
-            is_swap_completed: true,
+            fee_collected: fee,
+            amount_received,
+            status: offer.status,
         });
 
         Ok(())
@@ -697,6 +1610,7 @@ pub mod swap {
     // this is a clone of the relay_offer function
     // this fn is called maker deposit assets on EVM chain and we relay the offer to the Solana chain
     // this fn is executed by listening a event "tradeCreated" on EVM chain
+    #[allow(clippy::too_many_arguments)]
     pub fn relay_offer_clone(
         ctx: Context<RelayOfferClone>,
         id: u64,
@@ -707,6 +1621,8 @@ pub mod swap {
         is_taker_native: bool,
         chain_id: u64,
     ) -> Result<()> {
+        ctx.accounts.config.require_not_paused(PAUSE_RELAYS)?;
+
         // Populate InterchainOffer data using set_inner
         // ctx.accounts.interchain_offer.set_inner(InterchainOffer {
         //     trade_id: id,
@@ -731,15 +1647,54 @@ pub mod swap {
         interchain_offer.external_seller_evm = external_seller_evm;
         interchain_offer.is_seller_origin_sol = false;
         interchain_offer.is_taker_native = is_taker_native;
-        interchain_offer.is_swap_completed = false;
+        interchain_offer.status = OfferStatus::Relayed;
         interchain_offer.is_native = false;
         interchain_offer.chain_id = chain_id;
         interchain_offer.token_a_offered_amount = token_a_offered_amount;
         interchain_offer.token_b_wanted_amount = token_b_wanted_amount;
         interchain_offer.token_mint_a = ctx.accounts.token_mint_a.key();
         interchain_offer.fee_collected = 0;
+        interchain_offer.relayer = ctx.accounts.relayer.key();
+        // The relaying call is the first attestation; the offer only becomes
+        // fillable once the registry threshold (snapshotted here) is met.
+        interchain_offer.attestation_count = 1;
+        interchain_offer.required_attestations =
+            ctx.accounts.relayer_registry.required_attestations();
+        // Replaced by the buyer's own expiry once they fund the offer.
+        interchain_offer.expires_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(RELAYED_OFFER_FUNDING_WINDOW)
+            .ok_or(P2PError::MathOverflow)?;
         interchain_offer.bump = ctx.bumps.interchain_offer;
 
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.trade_id = id;
+        attestation.digest = evm_trade_digest(
+            ctx.program_id,
+            id,
+            chain_id,
+            &external_seller_evm,
+            &external_seller_sol,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            is_taker_native,
+            &ctx.accounts.token_mint_a.key(),
+        );
+        attestation.attesters = vec![Attester {
+            relayer: ctx.accounts.relayer.key(),
+            matches: true,
+        }];
+        attestation.required_attestations = interchain_offer.required_attestations;
+        attestation.bump = ctx.bumps.attestation;
+
+        emit!(TradeAttestedEvent {
+            trade_id: id,
+            external_seller_sol,
+            relayer: ctx.accounts.relayer.key(),
+            attestation_count: interchain_offer.attestation_count,
+            required_attestations: interchain_offer.required_attestations,
+        });
+
         // emit an event
         // this event will be listened by the Solana chain
         // and the Solana chain will create a new offer account
@@ -750,22 +1705,24 @@ pub mod swap {
             external_seller_evm,
             is_seller_origin_sol: false,
             is_taker_native,
-            is_swap_completed: false,
+            status: OfferStatus::Relayed,
             is_native: false,
             chain_id,
             token_a_offered_amount,
             token_b_wanted_amount,
             token_mint_a: ctx.accounts.token_mint_a.key(),
             fee_collected: 0,
+            relayer: ctx.accounts.relayer.key(),
+            evm_signer: [0; 20],
         });
 
         msg!(
             "Relay offer completed for trade id: {}, external_seller_sol: {}, 
-        external_seller_evm : {:?}, is_swap_completed: {}, token_a_offered_amount: {},token_b_wanted_amount: {}, is_taker_native: {}",
+        external_seller_evm : {:?}, status: {:?}, token_a_offered_amount: {},token_b_wanted_amount: {}, is_taker_native: {}",
             id,
             external_seller_sol,
             external_seller_evm,
-            false,
+            OfferStatus::Relayed,
             token_a_offered_amount,
             token_b_wanted_amount,
             is_taker_native
@@ -773,276 +1730,3521 @@ pub mod swap {
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct RelayOfferClone<'info> {
-    #[account(mut)]
-    pub maker: Signer<'info>,
+    /// Interchain => Origin is EVM
+    /// Additional registered relayers confirm a trade relayed by
+    /// `relay_offer_clone`. Matching attestations count towards the quorum;
+    /// mismatching ones are recorded and flagged with an event, and freeze the
+    /// trade once as many relayers dispute it as are needed to confirm it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn attest_evm_trade(
+        ctx: Context<AttestEvmTrade>,
+        id: u64,
+        external_seller_evm: [u8; 20],
+        external_seller_sol: Pubkey,
+        token_a_offered_amount: u64,
+        token_b_wanted_amount: u64,
+        is_taker_native: bool,
+        chain_id: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.require_not_paused(PAUSE_RELAYS)?;
+        // The EVM signature already stands in for the quorum.
+        require!(
+            ctx.accounts.attestation.required_attestations > 0,
+            P2PError::SignedTradeAttestation
+        );
 
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+        let relayer = ctx.accounts.relayer.key();
+        let registry = &ctx.accounts.relayer_registry;
+        let attestation = &mut ctx.accounts.attestation;
+        // Votes of relayers since removed from the registry no longer count,
+        // which also keeps the record within `MAX_RELAYERS` across rotations.
+        attestation
+            .attesters
+            .retain(|attester| registry.is_relayer(&attester.relayer));
+        require!(
+            !attestation
+                .attesters
+                .iter()
+                .any(|attester| attester.relayer == relayer),
+            P2PError::DuplicateAttestation
+        );
 
-    #[account(
-        init,
-        payer = maker,
-        // space: big enough for all fields
-        space = 8 + InterchainOffer::SIZE,
-        seeds = [b"InterChainoffer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
+        let digest = evm_trade_digest(
+            ctx.program_id,
+            id,
+            chain_id,
+            &external_seller_evm,
+            &external_seller_sol,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            is_taker_native,
+            &ctx.accounts.token_mint_a.key(),
+        );
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-}
+        let matches = digest == attestation.digest;
+        attestation.attesters.push(Attester { relayer, matches });
+        let attestation_count = attestation.matching();
+        let conflicts = attestation.conflicts();
+
+        // A finalized or refunded offer is closed; late attestations are still
+        // recorded and logged.
+        let offer_info = ctx.accounts.interchain_offer.to_account_info();
+        if offer_info.owner == ctx.program_id && !offer_info.data_is_empty() {
+            let mut interchain_offer =
+                InterchainOffer::try_deserialize(&mut &offer_info.data.borrow()[..])?;
+            interchain_offer.attestation_count = attestation_count;
+            // A quorum of conflicting attestations freezes the trade; a funded
+            // buyer can still get their deposit back once it expires.
+            if conflicts >= attestation.required_attestations
+                && interchain_offer
+                    .status
+                    .can_transition_to(OfferStatus::Disputed)
+            {
+                interchain_offer.status.transition(OfferStatus::Disputed)?;
+            }
+            interchain_offer.try_serialize(&mut &mut offer_info.data.borrow_mut()[..])?;
+        }
 
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct TakeInterchainOriginSolOffer<'info> {
-    #[account(mut)]
-    pub seller_sol: Signer<'info>, // seller origin on sol chain, who deposited the asset into contract
+        if !matches {
+            emit!(ConflictingAttestationEvent {
+                trade_id: id,
+                external_seller_sol,
+                relayer,
+                expected_digest: attestation.digest,
+                submitted_digest: digest,
+                conflicts,
+            });
 
-    #[account(mut)]
-    pub external_buyer_sol: SystemAccount<'info>, // Buyer : UserB's solana address who is taking the offer
+            msg!("Conflicting attestation for trade id: {}", id);
 
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+            return Ok(());
+        }
 
-    // #[account()]
-    // pub token_mint_b: Account<'info, Mint>,
-    #[account(
-        mut,
-        close = seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
-        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    #[account(
-        mut,
-        seeds = [b"vault-native"],
-        bump
-    )]
-    pub vault_native: AccountInfo<'info>,
+        emit!(TradeAttestedEvent {
+            trade_id: id,
+            external_seller_sol,
+            relayer,
+            attestation_count,
+            required_attestations: attestation.required_attestations,
+        });
 
-    #[account(
-        mut,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = global_authority,
-    )]
-    pub vault_spl: Account<'info, TokenAccount>,
+        Ok(())
+    }
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// It does not need additional validation because it's derived using `seeds = [b"global-authority"]`.
-    #[account(
-        mut,
-        seeds = [b"global-authority"],
-        bump
-    )]
-    pub global_authority: AccountInfo<'info>,
+    /// Interchain => Origin is EVM
+    /// Permissionless alternative to `relay_offer_clone`: the trade is
+    /// authenticated by a registered EVM signer's secp256k1 signature over
+    /// `evm_trade_message`, supplied as a Secp256k1 precompile instruction
+    /// immediately before this one. The signature stands in for the relayer
+    /// quorum. Each `(external_seller_sol, id)` can only be relayed once.
+    #[allow(clippy::too_many_arguments)]
+    pub fn relay_signed_evm_trade(
+        ctx: Context<RelaySignedEvmTrade>,
+        id: u64,
+        external_seller_evm: [u8; 20],
+        external_seller_sol: Pubkey,
+        token_a_offered_amount: u64,
+        token_b_wanted_amount: u64,
+        is_taker_native: bool,
+        chain_id: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.require_not_paused(PAUSE_RELAYS)?;
 
-    /// Taker's associated token account for receiving maker's SPL deposit.
-    #[account(
-        mut,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = external_buyer_sol,
-    )]
-    pub external_buyer_sol_token_account_a: Account<'info, TokenAccount>, // token account of seller on sol chain where he received the asset
+        let message = evm_trade_message(
+            ctx.program_id,
+            id,
+            chain_id,
+            &external_seller_evm,
+            &external_seller_sol,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            is_taker_native,
+            &ctx.accounts.token_mint_a.key(),
+        );
+        let evm_signer = verify_secp256k1_instruction(&ctx.accounts.instructions_sysvar, &message)?;
+        require!(
+            ctx.accounts.relayer_registry.is_evm_signer(&evm_signer),
+            P2PError::UnknownEvmSigner
+        );
 
-    // #[account(
-    //     mut,
-    //     associated_token::mint = token_mint_b,
-    //     associated_token::authority = external_seller_sol,
-    // )]
-    // pub taker_token_account_b: Account<'info, TokenAccount>,
+        let interchain_offer = &mut ctx.accounts.interchain_offer;
+        interchain_offer.trade_id = id;
+        interchain_offer.external_seller_sol = external_seller_sol;
+        interchain_offer.external_seller_evm = external_seller_evm;
+        interchain_offer.is_seller_origin_sol = false;
+        interchain_offer.is_taker_native = is_taker_native;
+        interchain_offer.status = OfferStatus::Relayed;
+        interchain_offer.is_native = false;
+        interchain_offer.chain_id = chain_id;
+        interchain_offer.token_a_offered_amount = token_a_offered_amount;
+        interchain_offer.token_b_wanted_amount = token_b_wanted_amount;
+        interchain_offer.token_mint_a = ctx.accounts.token_mint_a.key();
+        interchain_offer.fee_collected = 0;
+        interchain_offer.relayer = ctx.accounts.payer.key();
+        interchain_offer.attestation_count = 1;
+        interchain_offer.required_attestations = 1;
+        interchain_offer.evm_signer = evm_signer;
+        // Replaced by the buyer's own expiry once they fund the offer.
+        interchain_offer.expires_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(RELAYED_OFFER_FUNDING_WINDOW)
+            .ok_or(P2PError::MathOverflow)?;
+        interchain_offer.bump = ctx.bumps.interchain_offer;
 
-    // #[account(
-    //     mut,
-    //     token::mint = token_mint_b,
-    //     token::authority = maker
-    // )]
-    // pub maker_token_account_b: Account<'info, TokenAccount>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-}
+        // The attestation PDA outlives the offer, so the same signed payload
+        // cannot re-create the trade once it is finalized or refunded.
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.trade_id = id;
+        attestation.digest = anchor_lang::solana_program::hash::hash(&message).to_bytes();
+        attestation.attesters = Vec::new();
+        attestation.required_attestations = 0;
+        attestation.bump = ctx.bumps.attestation;
 
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct TakeInterchainOffer<'info> {
-    #[account(mut)]
-    pub external_seller_sol: Signer<'info>, // Seller : UserA's solana address
+        emit!(RelayEvmTradeEvent {
+            trade_id: id,
+            external_seller_sol,
+            external_seller_evm,
+            is_seller_origin_sol: false,
+            is_taker_native,
+            status: OfferStatus::Relayed,
+            is_native: false,
+            chain_id,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            token_mint_a: ctx.accounts.token_mint_a.key(),
+            fee_collected: 0,
+            relayer: ctx.accounts.payer.key(),
+            evm_signer,
+        });
 
-    #[account(mut)]
-    pub buyer_sol: SystemAccount<'info>, // Buyer : UserB's solana address
+        msg!(
+            "Signed relay completed for trade id: {}, evm_signer: {:?}",
+            id,
+            evm_signer
+        );
 
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+        Ok(())
+    }
 
-    // #[account()]
-    // pub token_mint_b: Account<'info, Mint>,
-    #[account(
-        mut,
-        close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
-        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub offer: Account<'info, InterchainOffer>,
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    #[account(
-        mut,
-        seeds = [b"vault-native"],
-        bump
-    )]
-    pub vault_native: AccountInfo<'info>,
+    /// Intra-Chain
+    /// Permissionless crank: once an offer has expired anyone can send the
+    /// escrow back to the maker and close the offer account.
+    pub fn refund_expired_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundExpiredOffer<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.offer.key();
 
-    #[account(
-        mut,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = global_authority,
-    )]
-    pub vault_spl: Account<'info, TokenAccount>,
+        let offer = &mut ctx.accounts.offer;
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// It does not need additional validation because it's derived using `seeds = [b"global-authority"]`.
-    #[account(
-        mut,
-        seeds = [b"global-authority"],
-        bump
-    )]
-    pub global_authority: AccountInfo<'info>,
+        offer.status.transition(OfferStatus::Expired)?;
+        require!(
+            Clock::get()?.unix_timestamp >= offer.expires_at,
+            P2PError::OfferNotExpired
+        );
 
-    /// Taker's associated token account for receiving maker's SPL deposit.
-    #[account(
-        mut,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = external_seller_sol,
-    )]
-    pub external_seller_sol_token_account_a: Account<'info, TokenAccount>,
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts.vault_ledger.debit(offer.remaining_a)?;
+
+        // The escrow is its own authority.
+        let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[ctx.bumps.escrow_spl]];
+        let signer_seeds = [seeds];
+
+        match &ctx.accounts.maker_token_account_a {
+            Some(maker_token_account_a) => {
+                transfer_tokens(
+                    &ctx.accounts.token_program,
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    &ctx.accounts.token_mint_a,
+                    maker_token_account_a.to_account_info(),
+                    ctx.accounts.escrow_spl.to_account_info(),
+                    ctx.remaining_accounts,
+                    offer.remaining_a,
+                    &signer_seeds,
+                )?;
 
-    // #[account(
-    //     mut,
-    //     associated_token::mint = token_mint_b,
-    //     associated_token::authority = external_seller_sol,
-    // )]
-    // pub taker_token_account_b: Account<'info, TokenAccount>,
+                close_token_escrow(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.token_mint_a,
+                    &ctx.accounts.escrow_spl,
+                    &ctx.accounts.maker.to_account_info(),
+                    &signer_seeds,
+                )?;
+            }
+            // Closing a wSOL escrow unwraps all of it, rent included, to the maker.
+            None => {
+                require!(offer.is_native, P2PError::MissingTokenAccount);
+                token_interface::close_account(CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::CloseAccount {
+                        account: ctx.accounts.escrow_spl.to_account_info(),
+                        destination: ctx.accounts.maker.to_account_info(),
+                        authority: ctx.accounts.escrow_spl.to_account_info(),
+                    },
+                    &signer_seeds,
+                ))?;
+            }
+        }
 
-    // #[account(
-    //     mut,
-    //     token::mint = token_mint_b,
-    //     token::authority = maker
-    // )]
-    // pub maker_token_account_b: Account<'info, TokenAccount>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-}
+        msg!(
+            "Expired intrachain offer {} refunded {} to maker {}.",
+            offer.id,
+            offer.remaining_a,
+            offer.maker
+        );
 
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct TakeOffer<'info> {
-    #[account(mut)]
-    pub taker: Signer<'info>,
+        let amount_refunded = net_of_transfer_fee(&ctx.accounts.token_mint_a, offer.remaining_a)?;
 
-    #[account(mut)]
-    pub maker: SystemAccount<'info>,
+        emit!(ExpiredOfferRefundedEvent {
+            id: offer.id,
+            depositor: offer.maker,
+            amount_refunded,
+            is_native: offer.is_native,
+            status: offer.status,
+        });
 
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+        Ok(())
+    }
 
-    #[account()]
-    pub token_mint_b: Account<'info, Mint>,
+    /// Interchain => Origin is EVM chain
+    /// Permissionless crank: returns an expired buyer deposit to `buyer_sol`.
+    /// An offer that was relayed but never funded is simply closed. Either way
+    /// the offer rent goes back to the relayer who paid it.
+    pub fn refund_expired_interchain_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundExpiredInterchainOffer<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.offer.key();
 
-    #[account(
-        mut,
-        close = maker,  // This tells Anchor to close the offer account and send its lamports to the maker.
-        seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub offer: Account<'info, Offer>,
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    #[account(
-        mut,
-        seeds = [b"vault-native"],
-        bump
-    )]
-    pub vault_native: AccountInfo<'info>,
+        let offer = &mut ctx.accounts.offer;
 
-    #[account(
-        mut,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = global_authority,
-    )]
-    pub vault_spl: Account<'info, TokenAccount>,
+        offer.status.transition(OfferStatus::Expired)?;
+        require!(
+            Clock::get()?.unix_timestamp >= offer.expires_at,
+            P2PError::OfferNotExpired
+        );
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// It does not need additional validation because it's derived using `seeds = [b"global-authority"]`.
-    #[account(
-        mut,
-        seeds = [b"global-authority"],
-        bump
-    )]
-    pub global_authority: AccountInfo<'info>,
+        let amount_refunded = if offer.deposited_amount == 0 {
+            // A relayed offer the buyer never funded has nothing in escrow;
+            // closing it only hands the rent back to the relayer.
+            0
+        } else if offer.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let buyer_sol = ctx
+                .accounts
+                .buyer_sol
+                .as_ref()
+                .ok_or(P2PError::InvalidDepositor)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+
+            // Return exactly what the buyer deposited.
+            // Refuses to pay out more than the mint's escrow is known to owe.
+            ctx.accounts
+                .vault_ledger
+                .as_mut()
+                .ok_or(P2PError::MissingVaultLedger)?
+                .debit(offer.deposited_amount)?;
 
-    /// Taker's associated token account for receiving maker's SPL deposit.
-    #[account(
-        mut,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = taker,
-    )]
-    pub taker_token_account_a: Account<'info, TokenAccount>,
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &escrow_native.key(),
+                &buyer_sol.key(),
+                offer.deposited_amount,
+            );
 
-    #[account(
-        mut,
-        associated_token::mint = token_mint_b,
-        associated_token::authority = taker,
-    )]
-    pub taker_token_account_b: Account<'info, TokenAccount>,
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    escrow_native.to_account_info(),
+                    buyer_sol.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
 
-    #[account(
-        mut,
-        token::mint = token_mint_b,
-        token::authority = maker
-    )]
-    pub maker_token_account_b: Account<'info, TokenAccount>,
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &buyer_sol.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-}
+            offer.deposited_amount
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            let token_mint_a = ctx
+                .accounts
+                .token_mint_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenMint)?;
+            let buyer_sol_token_account_a = ctx
+                .accounts
+                .buyer_sol_token_account_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+            let buyer_sol = ctx
+                .accounts
+                .buyer_sol
+                .as_ref()
+                .ok_or(P2PError::InvalidDepositor)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            // Return exactly what the buyer deposited.
+            // Refuses to pay out more than the mint's escrow is known to owe.
+            ctx.accounts
+                .vault_ledger
+                .as_mut()
+                .ok_or(P2PError::MissingVaultLedger)?
+                .debit(offer.deposited_amount)?;
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                token_mint_a,
+                buyer_sol_token_account_a.to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                offer.deposited_amount,
+                &signer_seeds,
+            )?;
 
-/// Context for depositing raw SOL
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct MakeOfferNative<'info> {
-    /// Person who deposits the SOL
-    #[account(mut)]
-    pub maker: Signer<'info>,
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                token_mint_a,
+                escrow_spl,
+                &buyer_sol.to_account_info(),
+                &signer_seeds,
+            )?;
 
-    /// Just pass in any valid mint for 'A' and 'B' if you like.  
-    /// Even if `is_native`, we’re not transferring SPL here.  
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+            net_of_transfer_fee(token_mint_a, offer.deposited_amount)?
+        };
 
-    #[account()]
-    pub token_mint_b: Account<'info, Mint>,
+        msg!(
+            "Expired interchain offer {} refunded {} to buyer {}.",
+            offer.trade_id,
+            offer.deposited_amount,
+            offer.buyer_sol
+        );
 
-    /// The Offer PDA storing trade details
-    #[account(
-        init,
-        payer = maker,
-        // space: big enough for all fields
-        space = 8 + Offer::SIZE,
-        seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub offer: Account<'info, Offer>,
+        emit!(ExpiredOfferRefundedEvent {
+            id: offer.trade_id,
+            depositor: offer.buyer_sol,
+            amount_refunded,
+            is_native: offer.is_native,
+            status: offer.status,
+        });
 
-    /// A simple system-owned vault account to store raw SOL
-    // #[account(
+        Ok(())
+    }
+
+    /// Interchain => Origin is SOL chain
+    /// Permissionless crank: returns an expired seller deposit to `seller_sol`.
+    pub fn refund_expired_interchain_origin_sol_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundExpiredInterchainOriginSolOffer<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.interchain_origin_sol_offer.key();
+
+        let offer = &mut ctx.accounts.interchain_origin_sol_offer;
+
+        offer.status.transition(OfferStatus::Expired)?;
+        require!(
+            Clock::get()?.unix_timestamp >= offer.expires_at,
+            P2PError::OfferNotExpired
+        );
+
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts
+            .vault_ledger
+            .debit(offer.token_a_offered_amount)?;
+
+        let amount_refunded = if offer.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &escrow_native.key(),
+                &ctx.accounts.seller_sol.key(),
+                offer.token_a_offered_amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    escrow_native.to_account_info(),
+                    ctx.accounts.seller_sol.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &ctx.accounts.seller_sol.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
+
+            offer.token_a_offered_amount
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            let token_mint_a = ctx
+                .accounts
+                .token_mint_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenMint)?;
+            let seller_sol_token_account_a = ctx
+                .accounts
+                .seller_sol_token_account_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                token_mint_a,
+                seller_sol_token_account_a.to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                offer.token_a_offered_amount,
+                &signer_seeds,
+            )?;
+
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                token_mint_a,
+                escrow_spl,
+                &ctx.accounts.seller_sol.to_account_info(),
+                &signer_seeds,
+            )?;
+
+            net_of_transfer_fee(token_mint_a, offer.token_a_offered_amount)?
+        };
+
+        msg!(
+            "Expired interchain origin sol offer {} refunded {} to seller {}.",
+            offer.trade_id,
+            offer.token_a_offered_amount,
+            offer.seller_sol
+        );
+
+        emit!(ExpiredOfferRefundedEvent {
+            id: offer.trade_id,
+            depositor: offer.seller_sol,
+            amount_refunded,
+            is_native: offer.is_native,
+            status: offer.status,
+        });
+
+        Ok(())
+    }
+
+    /// Interchain => Origin is SOl chain
+    /// Records the EVM buyer matched to an origin-SOL deposit. Authorised by the
+    /// seller or a registered relayer; `finalize_interchain_origin_sol_offer`
+    /// then only pays out to `external_buyer_sol`.
+    pub fn bind_interchain_buyer(
+        ctx: Context<BindInterchainBuyer>,
+        _id: u64,
+        external_buyer_sol: Pubkey,
+        external_buyer_evm: [u8; 20],
+        chain_id: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.require_not_paused(PAUSE_RELAYS)?;
+
+        let authority = ctx.accounts.authority.key();
+        let is_relayer = ctx
+            .accounts
+            .relayer_registry
+            .as_ref()
+            .is_some_and(|registry| registry.is_relayer(&authority));
+        require!(
+            authority == ctx.accounts.seller_sol.key() || is_relayer,
+            P2PError::UnauthorizedBinder
+        );
+
+        let offer = &mut ctx.accounts.interchain_origin_sol_offer;
+
+        require!(
+            offer.status == OfferStatus::Funded,
+            P2PError::InvalidStateTransition
+        );
+        require!(
+            Clock::get()?.unix_timestamp < offer.expires_at,
+            P2PError::OfferExpired
+        );
+        require!(
+            offer.external_buyer_sol == Pubkey::default(),
+            P2PError::BuyerAlreadyBound
+        );
+        require!(
+            external_buyer_sol != Pubkey::default(),
+            P2PError::InvalidBuyer
+        );
+        // Prevent a maker from filling their own offer.
+        require!(
+            external_buyer_sol != offer.seller_sol,
+            P2PError::MakerAndTakerCannotBeSame
+        );
+
+        offer.external_buyer_sol = external_buyer_sol;
+        offer.external_buyer_evm = external_buyer_evm;
+        offer.chain_id = chain_id;
+
+        emit!(InterchainBuyerBoundEvent {
+            id: offer.trade_id,
+            seller_sol: offer.seller_sol,
+            external_buyer_sol,
+            external_buyer_evm,
+            chain_id,
+            bound_by: authority,
+        });
+
+        Ok(())
+    }
+
+    /// Interchain => Origin is SOl chain
+    /// Seller takes back an origin-SOL deposit the EVM counterparty never
+    /// filled. Allowed `ORIGIN_SOL_RECLAIM_DELAY` after the deposit, or
+    /// `ORIGIN_SOL_COSIGNED_RECLAIM_DELAY` when a registered relayer co-signs
+    /// that no EVM-side fill happened. Once a buyer is bound they may already
+    /// have paid on EVM, so without a co-signer the seller must also wait for
+    /// `expires_at`.
+    pub fn reclaim_interchain_origin_sol_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReclaimInterchainOriginSolOffer<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.interchain_origin_sol_offer.key();
+
+        let offer = &mut ctx.accounts.interchain_origin_sol_offer;
+
+        offer.status.transition(OfferStatus::Cancelled)?;
+
+        let relayer = match (&ctx.accounts.relayer, &ctx.accounts.relayer_registry) {
+            (Some(relayer), Some(registry)) => {
+                require!(
+                    registry.is_relayer(&relayer.key()),
+                    P2PError::UnauthorizedRelayer
+                );
+                Some(relayer.key())
+            }
+            (None, _) => None,
+            (Some(_), None) => return err!(P2PError::UnauthorizedRelayer),
+        };
+        let reclaimable_at = if relayer.is_some() {
+            offer.created_at + ORIGIN_SOL_COSIGNED_RECLAIM_DELAY
+        } else if offer.external_buyer_sol != Pubkey::default() {
+            (offer.created_at + ORIGIN_SOL_RECLAIM_DELAY).max(offer.expires_at)
+        } else {
+            offer.created_at + ORIGIN_SOL_RECLAIM_DELAY
+        };
+        require!(
+            Clock::get()?.unix_timestamp >= reclaimable_at,
+            P2PError::ReclaimTooEarly
+        );
+
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts
+            .vault_ledger
+            .debit(offer.token_a_offered_amount)?;
+
+        let amount_refunded = if offer.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &escrow_native.key(),
+                &ctx.accounts.seller_sol.key(),
+                offer.token_a_offered_amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    escrow_native.to_account_info(),
+                    ctx.accounts.seller_sol.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &ctx.accounts.seller_sol.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
+
+            offer.token_a_offered_amount
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            let token_mint_a = ctx
+                .accounts
+                .token_mint_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenMint)?;
+            let seller_sol_token_account_a = ctx
+                .accounts
+                .seller_sol_token_account_a
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                token_mint_a,
+                seller_sol_token_account_a.to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                offer.token_a_offered_amount,
+                &signer_seeds,
+            )?;
+
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                token_mint_a,
+                escrow_spl,
+                &ctx.accounts.seller_sol.to_account_info(),
+                &signer_seeds,
+            )?;
+
+            net_of_transfer_fee(token_mint_a, offer.token_a_offered_amount)?
+        };
+
+        msg!(
+            "Interchain origin sol offer {} reclaimed {} by seller {}.",
+            offer.trade_id,
+            offer.token_a_offered_amount,
+            offer.seller_sol
+        );
+
+        emit!(InterchainOfferReclaimedEvent {
+            id: offer.trade_id,
+            seller_sol: offer.seller_sol,
+            amount_refunded,
+            is_native: offer.is_native,
+            relayer,
+            status: offer.status,
+        });
+
+        Ok(())
+    }
+
+    /// Interchain => HTLC
+    /// Locks native SOL against `hashlock` until `timelock`. `recipient` claims
+    /// by revealing the preimage, which lets the counterparty claim the matching
+    /// EVM-side HTLC; after `timelock` the depositor can reclaim instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn htlc_lock_native(
+        ctx: Context<HtlcLockNative>,
+        id: u64,
+        recipient: Pubkey,
+        counterparty_evm: [u8; 20],
+        amount: u64,
+        hashlock: [u8; 32],
+        hash_kind: HashlockKind,
+        timelock: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTERCHAIN_DEPOSITS)?;
+
+        require!(amount > 0, P2PError::InvalidAmount);
+        require!(
+            timelock > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.depositor.key(),
+            &ctx.accounts.escrow_native.key(),
+            amount,
+        );
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.depositor.to_account_info(),
+                ctx.accounts.escrow_native.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[],
+        )?;
+
+        // Fixed now so the claimable amount cannot drift from the EVM leg.
+        let fee = fee_amount(amount, ctx.accounts.config.interchain_origin_sol_fee_bps)?;
+
+        ctx.accounts.htlc.set_inner(HtlcOffer {
+            id,
+            depositor: ctx.accounts.depositor.key(),
+            recipient,
+            counterparty_evm,
+            token_mint: ctx.accounts.token_mint.key(),
+            is_native: true,
+            amount,
+            fee,
+            hashlock,
+            hash_kind,
+            timelock,
+            bump: ctx.bumps.htlc,
+        });
+
+        ctx.accounts
+            .vault_ledger
+            .credit(native_mint::ID, ctx.bumps.vault_ledger, amount)?;
+
+        emit!(HtlcLockedEvent {
+            id,
+            depositor: ctx.accounts.depositor.key(),
+            recipient,
+            counterparty_evm,
+            token_mint: ctx.accounts.token_mint.key(),
+            is_native: true,
+            amount,
+            fee,
+            hashlock,
+            hash_kind,
+            timelock,
+        });
+
+        Ok(())
+    }
+
+    /// Interchain => HTLC
+    /// SPL variant of `htlc_lock_native`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn htlc_lock_spl<'info>(
+        ctx: Context<'_, '_, '_, 'info, HtlcLockSpl<'info>>,
+        id: u64,
+        recipient: Pubkey,
+        counterparty_evm: [u8; 20],
+        amount: u64,
+        hashlock: [u8; 32],
+        hash_kind: HashlockKind,
+        timelock: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_not_paused(PAUSE_INTERCHAIN_DEPOSITS)?;
+
+        require!(amount > 0, P2PError::InvalidAmount);
+        require!(
+            timelock > Clock::get()?.unix_timestamp,
+            P2PError::InvalidExpiry
+        );
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.depositor_token_account.to_account_info(),
+            &ctx.accounts.token_mint,
+            ctx.accounts.escrow_spl.to_account_info(),
+            ctx.accounts.depositor.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            &[],
+        )?;
+
+        // A Token-2022 transfer fee is withheld from the deposit, so the offer
+        // records what the escrow actually received.
+        ctx.accounts.escrow_spl.reload()?;
+        let amount = ctx.accounts.escrow_spl.amount;
+
+        // Fixed now so the claimable amount cannot drift from the EVM leg.
+        let fee = fee_amount(amount, ctx.accounts.config.interchain_origin_sol_fee_bps)?;
+
+        ctx.accounts.htlc.set_inner(HtlcOffer {
+            id,
+            depositor: ctx.accounts.depositor.key(),
+            recipient,
+            counterparty_evm,
+            token_mint: ctx.accounts.token_mint.key(),
+            is_native: false,
+            amount,
+            fee,
+            hashlock,
+            hash_kind,
+            timelock,
+            bump: ctx.bumps.htlc,
+        });
+
+        ctx.accounts.vault_ledger.credit(
+            ctx.accounts.token_mint.key(),
+            ctx.bumps.vault_ledger,
+            amount,
+        )?;
+
+        emit!(HtlcLockedEvent {
+            id,
+            depositor: ctx.accounts.depositor.key(),
+            recipient,
+            counterparty_evm,
+            token_mint: ctx.accounts.token_mint.key(),
+            is_native: false,
+            amount,
+            fee,
+            hashlock,
+            hash_kind,
+            timelock,
+        });
+
+        Ok(())
+    }
+
+    /// Interchain => HTLC
+    /// Releases the lock to the recipient when `preimage` hashes to the
+    /// hashlock. Anyone may submit the preimage; the funds only go to the
+    /// recipient. The preimage is published in `HtlcClaimedEvent`.
+    ///
+    /// Never paused: once the preimage is public on the EVM side, a paused
+    /// claim would let the depositor refund after `timelock` and keep both legs.
+    pub fn htlc_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, HtlcClaim<'info>>,
+        _id: u64,
+        preimage: [u8; 32],
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.htlc.key();
+
+        let htlc = &ctx.accounts.htlc;
+
+        require!(
+            Clock::get()?.unix_timestamp < htlc.timelock,
+            P2PError::HtlcExpired
+        );
+        require!(
+            htlc.hash_kind.hash(&preimage) == htlc.hashlock,
+            P2PError::InvalidPreimage
+        );
+
+        // The fee was fixed and announced when the HTLC was locked.
+        let fee = htlc.fee;
+        let recipient_amount = htlc.amount.checked_sub(fee).ok_or(P2PError::MathOverflow)?;
+
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts.vault_ledger.debit(htlc.amount)?;
+
+        if htlc.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &escrow_native.key(),
+                &ctx.accounts.recipient.key(),
+                recipient_amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    escrow_native.to_account_info(),
+                    ctx.accounts.recipient.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+
+            if fee > 0 {
+                let fee_ix = anchor_lang::solana_program::system_instruction::transfer(
+                    &escrow_native.key(),
+                    &ctx.accounts.treasury.key(),
+                    fee,
+                );
+
+                anchor_lang::solana_program::program::invoke_signed(
+                    &fee_ix,
+                    &[
+                        escrow_native.to_account_info(),
+                        ctx.accounts.treasury.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                    signer_seeds,
+                )?;
+            }
+
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &ctx.accounts.depositor.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            let recipient_token_account = ctx
+                .accounts
+                .recipient_token_account
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+            let treasury_token_account = ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                &ctx.accounts.token_mint,
+                recipient_token_account.to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                recipient_amount,
+                &signer_seeds,
+            )?;
+
+            if fee > 0 {
+                transfer_tokens(
+                    &ctx.accounts.token_program,
+                    escrow_spl.to_account_info(),
+                    &ctx.accounts.token_mint,
+                    treasury_token_account.to_account_info(),
+                    escrow_spl.to_account_info(),
+                    ctx.remaining_accounts,
+                    fee,
+                    &signer_seeds,
+                )?;
+            }
+
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint,
+                escrow_spl,
+                &ctx.accounts.depositor.to_account_info(),
+                &signer_seeds,
+            )?;
+        }
+
+        msg!(
+            "HTLC {} claimed: {} released to recipient {}",
+            htlc.id,
+            recipient_amount,
+            htlc.recipient
+        );
+
+        let amount = if htlc.is_native {
+            recipient_amount
+        } else {
+            net_of_transfer_fee(&ctx.accounts.token_mint, recipient_amount)?
+        };
+
+        emit!(HtlcClaimedEvent {
+            id: htlc.id,
+            depositor: htlc.depositor,
+            recipient: htlc.recipient,
+            hashlock: htlc.hashlock,
+            preimage,
+            amount,
+            fee_collected: fee,
+        });
+
+        ctx.accounts
+            .htlc
+            .close(ctx.accounts.depositor.to_account_info())?;
+
+        Ok(())
+    }
+
+    /// Interchain => HTLC
+    /// Permissionless crank: once the timelock has passed without a claim, the
+    /// lock goes back to the depositor.
+    pub fn htlc_refund<'info>(
+        ctx: Context<'_, '_, '_, 'info, HtlcRefund<'info>>,
+        _id: u64,
+    ) -> Result<()> {
+        let offer_key = ctx.accounts.htlc.key();
+
+        let htlc = &ctx.accounts.htlc;
+
+        require!(
+            Clock::get()?.unix_timestamp >= htlc.timelock,
+            P2PError::HtlcNotExpired
+        );
+
+        // Refuses to pay out more than the mint's escrow is known to owe.
+        ctx.accounts.vault_ledger.debit(htlc.amount)?;
+
+        if htlc.is_native {
+            let escrow_native = ctx
+                .accounts
+                .escrow_native
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_native.ok_or(P2PError::MissingEscrow)?;
+            let seeds: &[&[u8]] = &[b"escrow-native", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &escrow_native.key(),
+                &ctx.accounts.depositor.key(),
+                htlc.amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    escrow_native.to_account_info(),
+                    ctx.accounts.depositor.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+
+            // Hand the escrow's rent back, closing it.
+            close_native_escrow(
+                escrow_native,
+                &ctx.accounts.depositor.to_account_info(),
+                &ctx.accounts.system_program,
+                signer_seeds,
+            )?;
+        } else {
+            let escrow_spl = ctx
+                .accounts
+                .escrow_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let escrow_bump = ctx.bumps.escrow_spl.ok_or(P2PError::MissingEscrow)?;
+            // The escrow is its own authority.
+            let seeds: &[&[u8]] = &[b"escrow", offer_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = [seeds];
+
+            let depositor_token_account = ctx
+                .accounts
+                .depositor_token_account
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                escrow_spl.to_account_info(),
+                &ctx.accounts.token_mint,
+                depositor_token_account.to_account_info(),
+                escrow_spl.to_account_info(),
+                ctx.remaining_accounts,
+                htlc.amount,
+                &signer_seeds,
+            )?;
+
+            close_token_escrow(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint,
+                escrow_spl,
+                &ctx.accounts.depositor.to_account_info(),
+                &signer_seeds,
+            )?;
+        }
+
+        msg!(
+            "HTLC {} refunded {} to depositor {}",
+            htlc.id,
+            htlc.amount,
+            htlc.depositor
+        );
+
+        let amount = if htlc.is_native {
+            htlc.amount
+        } else {
+            net_of_transfer_fee(&ctx.accounts.token_mint, htlc.amount)?
+        };
+
+        emit!(HtlcRefundedEvent {
+            id: htlc.id,
+            depositor: htlc.depositor,
+            amount,
+            is_native: htlc.is_native,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Creates the global `Config` PDA. Only the program's upgrade authority can
+    /// call this, which keeps the first admin from being front-run.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, features: u64) -> Result<()> {
+        ctx.accounts.config.set_inner(Config {
+            admin: ctx.accounts.authority.key(),
+            pending_admin: None,
+            version: CONFIG_VERSION,
+            features,
+            guardian: ctx.accounts.authority.key(),
+            paused: 0,
+            intrachain_fee_bps: 0,
+            interchain_origin_evm_fee_bps: 0,
+            interchain_origin_sol_fee_bps: 0,
+            bump: ctx.bumps.config,
+        });
+
+        emit!(ConfigUpdatedEvent {
+            admin: ctx.accounts.authority.key(),
+            version: CONFIG_VERSION,
+            features,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Replaces the feature toggles stored in `Config`.
+    pub fn update_config(ctx: Context<UpdateConfig>, features: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.features = features;
+
+        emit!(ConfigUpdatedEvent {
+            admin: config.admin,
+            version: config.version,
+            features,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// First step of an admin handover: records `new_admin` as pending. The
+    /// current admin stays in charge until `accept_admin` is called.
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_admin = Some(new_admin);
+
+        emit!(AdminProposedEvent {
+            admin: config.admin,
+            pending_admin: new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Second step of an admin handover, signed by the pending admin.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.pending_admin == Some(ctx.accounts.pending_admin.key()),
+            P2PError::UnauthorizedPendingAdmin
+        );
+
+        let previous_admin = config.admin;
+        config.admin = ctx.accounts.pending_admin.key();
+        config.pending_admin = None;
+
+        emit!(AdminTransferredEvent {
+            previous_admin,
+            new_admin: config.admin,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Sets the guardian, a hot key that can pause the program in an emergency.
+    pub fn set_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.guardian = guardian;
+
+        emit!(GuardianUpdatedEvent {
+            admin: config.admin,
+            guardian,
+        });
+
+        Ok(())
+    }
+
+    /// Admin / Guardian
+    /// Replaces the `PAUSE_*` bits. The admin may set any value; the guardian
+    /// may only pause more, never unpause.
+    pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let authority = ctx.accounts.authority.key();
+
+        if authority != config.admin {
+            require!(
+                authority == config.guardian && paused & config.paused == config.paused,
+                P2PError::UnauthorizedPauser
+            );
+        }
+        config.paused = paused;
+
+        emit!(PauseUpdatedEvent { authority, paused });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Allows `relayer` to call `relay_offer_clone`. Creates the registry on
+    /// first use.
+    pub fn add_relayer(ctx: Context<ManageRelayers>, relayer: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.relayer_registry;
+        registry.bump = ctx.bumps.relayer_registry;
+
+        require!(
+            !registry.is_relayer(&relayer),
+            P2PError::RelayerAlreadyRegistered
+        );
+        require!(
+            registry.relayers.len() < MAX_RELAYERS,
+            P2PError::RelayerRegistryFull
+        );
+        registry.relayers.push(relayer);
+
+        emit!(RelayerAddedEvent {
+            admin: ctx.accounts.admin.key(),
+            relayer,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Revokes `relayer`. Offers it already relayed are left untouched.
+    pub fn remove_relayer(ctx: Context<ManageRelayers>, relayer: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.relayer_registry;
+        let index = registry
+            .relayers
+            .iter()
+            .position(|r| *r == relayer)
+            .ok_or(P2PError::RelayerNotRegistered)?;
+        // Enough relayers must remain to reach the threshold.
+        require!(
+            registry.relayers.len() > registry.threshold as usize,
+            P2PError::InvalidThreshold
+        );
+        registry.relayers.swap_remove(index);
+
+        emit!(RelayerRemovedEvent {
+            admin: ctx.accounts.admin.key(),
+            relayer,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Sets how many matching relayer attestations an origin-EVM trade needs
+    /// before it can be finalized. Applies to trades relayed afterwards.
+    pub fn set_relayer_threshold(ctx: Context<ManageRelayers>, threshold: u8) -> Result<()> {
+        let registry = &mut ctx.accounts.relayer_registry;
+        require!(
+            threshold >= 1 && threshold as usize <= registry.relayers.len(),
+            P2PError::InvalidThreshold
+        );
+
+        registry.bump = ctx.bumps.relayer_registry;
+        registry.threshold = threshold;
+
+        emit!(RelayerThresholdUpdatedEvent {
+            admin: ctx.accounts.admin.key(),
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Accepts secp256k1 signatures from `evm_signer` in `relay_signed_evm_trade`.
+    pub fn add_evm_signer(ctx: Context<ManageRelayers>, evm_signer: [u8; 20]) -> Result<()> {
+        let registry = &mut ctx.accounts.relayer_registry;
+        registry.bump = ctx.bumps.relayer_registry;
+
+        require!(
+            !registry.is_evm_signer(&evm_signer),
+            P2PError::EvmSignerAlreadyRegistered
+        );
+        require!(
+            registry.evm_signers.len() < MAX_EVM_SIGNERS,
+            P2PError::EvmSignerSetFull
+        );
+        registry.evm_signers.push(evm_signer);
+
+        emit!(EvmSignerAddedEvent {
+            admin: ctx.accounts.admin.key(),
+            evm_signer,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Revokes `evm_signer`. Trades it already signed are left untouched.
+    pub fn remove_evm_signer(ctx: Context<ManageRelayers>, evm_signer: [u8; 20]) -> Result<()> {
+        let registry = &mut ctx.accounts.relayer_registry;
+        let index = registry
+            .evm_signers
+            .iter()
+            .position(|s| *s == evm_signer)
+            .ok_or(P2PError::EvmSignerNotRegistered)?;
+        registry.evm_signers.swap_remove(index);
+
+        emit!(EvmSignerRemovedEvent {
+            admin: ctx.accounts.admin.key(),
+            evm_signer,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Sets the protocol fee, in basis points, for each swap path.
+    pub fn set_fees(
+        ctx: Context<UpdateConfig>,
+        intrachain_fee_bps: u16,
+        interchain_origin_evm_fee_bps: u16,
+        interchain_origin_sol_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            intrachain_fee_bps <= MAX_FEE_BPS
+                && interchain_origin_evm_fee_bps <= MAX_FEE_BPS
+                && interchain_origin_sol_fee_bps <= MAX_FEE_BPS,
+            P2PError::FeeTooHigh
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.intrachain_fee_bps = intrachain_fee_bps;
+        config.interchain_origin_evm_fee_bps = interchain_origin_evm_fee_bps;
+        config.interchain_origin_sol_fee_bps = interchain_origin_sol_fee_bps;
+
+        emit!(FeesUpdatedEvent {
+            admin: config.admin,
+            intrachain_fee_bps,
+            interchain_origin_evm_fee_bps,
+            interchain_origin_sol_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Moves collected fees out of the treasury, either native SOL or SPL tokens
+    /// of `token_mint`.
+    pub fn withdraw_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
+        amount: u64,
+        is_native: bool,
+    ) -> Result<()> {
+        require!(amount > 0, P2PError::InvalidAmount);
+
+        let treasury_seeds: &[&[u8]] = &[b"treasury", &[ctx.bumps.treasury]];
+        let signer_seeds = &[treasury_seeds];
+
+        if is_native {
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.treasury.key(),
+                &ctx.accounts.destination.key(),
+                amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.treasury.to_account_info(),
+                    ctx.accounts.destination.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+        } else {
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                ctx.accounts.treasury_token_account.to_account_info(),
+                &ctx.accounts.token_mint,
+                ctx.accounts.destination_token_account.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+                ctx.remaining_accounts,
+                amount,
+                signer_seeds,
+            )?;
+        }
+
+        emit!(FeesWithdrawnEvent {
+            admin: ctx.accounts.admin.key(),
+            destination: ctx.accounts.destination.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount,
+            is_native,
+        });
+
+        Ok(())
+    }
+
+    /// Admin
+    /// Moves surplus of `token_mint` to the treasury: whatever the legacy
+    /// shared vault (`vault-native` or the `global-authority` ATA) holds beyond
+    /// what legacy offers may still be owed, see `VaultLedger`, plus anything
+    /// sent to the escrows of closed offers. The first `orphaned_escrows`
+    /// `(offer, escrow)` pairs of the remaining accounts are such escrows; they
+    /// are drained and closed. Any further remaining accounts are forwarded to
+    /// the mint's transfer hook.
+    pub fn sweep_surplus<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepSurplus<'info>>,
+        is_native: bool,
+        orphaned_escrows: u8,
+    ) -> Result<()> {
+        let pairs_len = usize::from(orphaned_escrows) * 2;
+        require!(
+            ctx.remaining_accounts.len() >= pairs_len,
+            P2PError::InvalidEscrow
+        );
+        let (escrow_pairs, hook_accounts) = ctx.remaining_accounts.split_at(pairs_len);
+
+        let mint = ctx.accounts.token_mint.key();
+        let ledger = &mut ctx.accounts.vault_ledger;
+        // A mint that never had an escrow has a fresh, empty ledger.
+        ledger.credit(mint, ctx.bumps.vault_ledger, 0)?;
+
+        let vault_balance = if is_native {
+            Some(
+                ctx.accounts
+                    .vault_native
+                    .lamports()
+                    .saturating_sub(Rent::get()?.minimum_balance(0)),
+            )
+        } else {
+            ctx.accounts.vault_spl.as_ref().map(|vault| vault.amount)
+        };
+        // Only legacy offers are paid from the shared vault, so anything beyond
+        // what they may still be owed is surplus.
+        let surplus = vault_balance.map_or(0, |balance| {
+            ledger.snapshot_legacy_vault(balance);
+            balance.saturating_sub(ledger.legacy_outstanding)
+        });
+        let legacy_outstanding = ledger.legacy_outstanding;
+
+        if surplus > 0 && is_native {
+            let vault_seeds: &[&[u8]] = &[b"vault-native", &[ctx.bumps.vault_native]];
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault_native.key(),
+                &ctx.accounts.treasury.key(),
+                surplus,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault_native.to_account_info(),
+                    ctx.accounts.treasury.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                &[vault_seeds],
+            )?;
+        } else if surplus > 0 {
+            let vault_spl = ctx
+                .accounts
+                .vault_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let treasury_token_account = ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+            let global_authority_seeds: &[&[u8]] =
+                &[b"global-authority", &[ctx.bumps.global_authority]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                vault_spl.to_account_info(),
+                &ctx.accounts.token_mint,
+                treasury_token_account.to_account_info(),
+                ctx.accounts.global_authority.to_account_info(),
+                hook_accounts,
+                surplus,
+                &[global_authority_seeds],
+            )?;
+        }
+
+        let mut orphaned: u64 = 0;
+        for pair in escrow_pairs.chunks_exact(2) {
+            let drained = drain_orphaned_escrow(
+                ctx.program_id,
+                ctx.accounts,
+                &pair[0],
+                &pair[1],
+                hook_accounts,
+            )?;
+            orphaned = orphaned
+                .checked_add(drained)
+                .ok_or(P2PError::MathOverflow)?;
+        }
+
+        msg!(
+            "Swept {} of {} surplus and {} from {} closed offers' escrows to the treasury; {} held back for legacy offers.",
+            surplus,
+            mint,
+            orphaned,
+            orphaned_escrows,
+            legacy_outstanding
+        );
+
+        emit!(SurplusSweptEvent {
+            admin: ctx.accounts.admin.key(),
+            token_mint: mint,
+            is_native,
+            amount: surplus,
+            legacy_outstanding,
+            orphaned,
+            escrows_closed: orphaned_escrows,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: settles an offer opened before per-offer
+    /// escrows, whose deposit still sits in the shared `vault-native` or
+    /// `global-authority` vault. Pays the recorded deposit back to the
+    /// depositor and closes the offer account to them. Deposits the legacy
+    /// layout did not record cannot be refunded this way.
+    pub fn refund_legacy_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundLegacyOffer<'info>>,
+    ) -> Result<()> {
+        let legacy = &ctx.accounts.legacy_offer;
+        let amount = legacy.amount;
+        require!(amount > 0, P2PError::NothingToRefund);
+
+        let vault_balance = if legacy.is_native {
+            ctx.accounts
+                .vault_native
+                .lamports()
+                .saturating_sub(Rent::get()?.minimum_balance(0))
+        } else {
+            ctx.accounts
+                .vault_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?
+                .amount
+        };
+        let ledger = &mut ctx.accounts.vault_ledger;
+        ledger.credit(legacy.token_mint, ctx.bumps.vault_ledger, 0)?;
+        ledger.snapshot_legacy_vault(vault_balance);
+        ledger.debit_legacy(amount)?;
+
+        if legacy.is_native {
+            let vault_seeds: &[&[u8]] = &[b"vault-native", &[ctx.bumps.vault_native]];
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault_native.key(),
+                &ctx.accounts.depositor.key(),
+                amount,
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault_native.to_account_info(),
+                    ctx.accounts.depositor.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                &[vault_seeds],
+            )?;
+        } else {
+            let vault_spl = ctx
+                .accounts
+                .vault_spl
+                .as_ref()
+                .ok_or(P2PError::MissingEscrow)?;
+            let depositor_token_account = ctx
+                .accounts
+                .depositor_token_account
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+            let global_authority_seeds: &[&[u8]] =
+                &[b"global-authority", &[ctx.bumps.global_authority]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                vault_spl.to_account_info(),
+                &ctx.accounts.token_mint,
+                depositor_token_account.to_account_info(),
+                ctx.accounts.global_authority.to_account_info(),
+                ctx.remaining_accounts,
+                amount,
+                &[global_authority_seeds],
+            )?;
+        }
+
+        msg!(
+            "Legacy offer {} refunded {} to {}.",
+            legacy.key(),
+            amount,
+            legacy.depositor
+        );
+
+        emit!(LegacyOfferRefundedEvent {
+            caller: ctx.accounts.caller.key(),
+            offer: legacy.key(),
+            depositor: legacy.depositor,
+            token_mint: legacy.token_mint,
+            amount,
+            is_native: legacy.is_native,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: compares `vault_ledger` against the escrows passed as
+    /// `(offer, escrow)` pairs in the remaining accounts and emits a
+    /// `SolvencyReportEvent`. Escrows left out are not counted, so the report
+    /// only shows solvency when every open escrow of the mint is passed.
+    pub fn check_solvency(ctx: Context<CheckSolvency>) -> Result<()> {
+        let ledger = &ctx.accounts.vault_ledger;
+        let (observed, escrows_checked) =
+            escrow_holdings(ctx.program_id, ledger.mint, ctx.remaining_accounts)?;
+
+        msg!(
+            "Vault ledger for {}: outstanding {}, observed {} across {} escrows.",
+            ledger.mint,
+            ledger.outstanding,
+            observed,
+            escrows_checked
+        );
+
+        emit!(SolvencyReportEvent {
+            mint: ledger.mint,
+            outstanding: ledger.outstanding,
+            observed,
+            escrows_checked,
+            solvent: observed >= ledger.outstanding,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// Upgrade authority of this program; becomes the first admin.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Swap>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ P2PError::UnauthorizedAdmin
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ P2PError::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ P2PError::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account()]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Protocol treasury PDA, see `TakeOffer::treasury`.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = destination,
+        token::token_program = token_program,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(is_native: bool)]
+pub struct SweepSurplus<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ P2PError::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The wrapped SOL mint when sweeping native SOL.
+    #[account(
+        constraint = !is_native || token_mint.key() == native_mint::ID @ P2PError::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: Legacy shared native vault, a zero-data system account.
+    #[account(
+        mut,
+        seeds = [b"vault-native"],
+        bump
+    )]
+    pub vault_native: UncheckedAccount<'info>,
+
+    /// CHECK: Authority of the legacy shared token vaults.
+    #[account(
+        seeds = [b"global-authority"],
+        bump
+    )]
+    pub global_authority: UncheckedAccount<'info>,
+
+    /// Legacy shared token vault of `token_mint`, if it exists.
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = global_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Protocol treasury PDA, see `TakeOffer::treasury`.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Receives swept tokens. Only needed when tokens are swept.
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct RefundLegacyOffer<'info> {
+    /// Anyone may settle a legacy offer. They pay the transaction fee and, if
+    /// the mint has none yet, the vault ledger's rent.
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// Offer account in a pre-escrow layout; its rent goes back to the
+    /// depositor with the refund.
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor @ P2PError::InvalidDepositor,
+        has_one = token_mint @ P2PError::InvalidTokenMint
+    )]
+    pub legacy_offer: Account<'info, LegacyEscrow>,
+
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    /// The wrapped SOL mint when refunding native SOL.
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Tracks what the legacy shared vault of `token_mint` may still owe.
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: Legacy shared native vault, a zero-data system account.
+    #[account(
+        mut,
+        seeds = [b"vault-native"],
+        bump
+    )]
+    pub vault_native: UncheckedAccount<'info>,
+
+    /// CHECK: Authority of the legacy shared token vaults.
+    #[account(
+        seeds = [b"global-authority"],
+        bump
+    )]
+    pub global_authority: UncheckedAccount<'info>,
+
+    /// Legacy shared token vault of `token_mint`; required for token refunds.
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = global_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required for token refunds.
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CheckSolvency<'info> {
+    #[account(
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRelayers<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ P2PError::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RelayerRegistry::SIZE,
+        seeds = [b"relayer-registry"],
+        bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    /// Admin or guardian.
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct RefundExpiredOffer<'info> {
+    /// Anyone may crank an expired offer; they only pay the transaction fee.
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Writable so withheld Token-2022 fees can be harvested from the escrow.
+    #[account(mut)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = maker,  // Rent goes back to the maker together with the escrow.
+        has_one = maker @ P2PError::UnauthorizedMaker,
+        has_one = token_mint_a @ P2PError::InvalidTokenMint,
+        seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+    /// Per-offer token escrow, holding wSOL for SOL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(offer.is_native, offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Maker's token account receiving the refunded deposit. Left out to
+    /// receive a wSOL deposit as lamports.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct RefundExpiredInterchainOffer<'info> {
+    /// Anyone may crank an expired offer; they only pay the transaction fee.
+    pub caller: Signer<'info>,
+
+    pub external_seller_sol: SystemAccount<'info>,
+
+    /// Receives the offer account rent, which the relayer paid when the offer
+    /// was relayed.
+    #[account(mut)]
+    pub relayer: SystemAccount<'info>,
+
+    /// Only passed for funded offers.
+    #[account(
+        mut,
+        constraint = buyer_sol.key() == offer.buyer_sol @ P2PError::InvalidDepositor
+    )]
+    pub buyer_sol: Option<SystemAccount<'info>>,
+
+    /// Only passed for SPL offers. Writable so withheld Token-2022 fees can be
+    /// harvested from the escrow.
+    #[account(
+        mut,
+        constraint = token_mint_a.key() == offer.token_mint_a @ P2PError::InvalidTokenMint
+    )]
+    pub token_mint_a: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = relayer,
+        has_one = relayer @ P2PError::UnauthorizedRelayer,
+        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, InterchainOffer>,
+    /// CHECK: Per-offer native escrow. Only passed for funded native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for funded SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint. Only passed for
+    /// funded offers.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(offer.is_native, offer.deposited_mint) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Option<Account<'info, VaultLedger>>,
+
+    /// Buyer's token account receiving the refunded deposit. Only passed for
+    /// funded SPL offers.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = buyer_sol,
+        token::token_program = token_program,
+    )]
+    pub buyer_sol_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct RefundExpiredInterchainOriginSolOffer<'info> {
+    /// Anyone may crank an expired offer; they only pay the transaction fee.
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub seller_sol: SystemAccount<'info>,
+
+    /// Only passed for SPL offers. Writable so withheld Token-2022 fees can be
+    /// harvested from the escrow.
+    #[account(
+        mut,
+        constraint = token_mint_a.key() == interchain_origin_sol_offer.token_mint_a @ P2PError::InvalidTokenMint
+    )]
+    pub token_mint_a: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = seller_sol,  // Rent goes back to the seller together with the escrow.
+        has_one = seller_sol @ P2PError::InvalidDepositor,
+        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+    /// CHECK: Per-offer native escrow. Only passed for native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", interchain_origin_sol_offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", interchain_origin_sol_offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(interchain_origin_sol_offer.is_native, interchain_origin_sol_offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Seller's token account receiving the refunded deposit. Only passed for
+    /// SPL offers.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = seller_sol,
+        token::token_program = token_program,
+    )]
+    pub seller_sol_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct BindInterchainBuyer<'info> {
+    /// The seller, or a registered relayer.
+    pub authority: Signer<'info>,
+
+    pub seller_sol: SystemAccount<'info>,
+
+    /// Required when `authority` is a relayer.
+    #[account(
+        seeds = [b"relayer-registry"],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Option<Account<'info, RelayerRegistry>>,
+
+    #[account(
+        mut,
+        has_one = seller_sol @ P2PError::InvalidDepositor,
+        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct ReclaimInterchainOriginSolOffer<'info> {
+    #[account(mut)]
+    pub seller_sol: Signer<'info>,
+
+    /// Optional co-signer attesting that the trade was never filled on the EVM side.
+    pub relayer: Option<Signer<'info>>,
+
+    #[account(
+        seeds = [b"relayer-registry"],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Option<Account<'info, RelayerRegistry>>,
+
+    /// Only passed for SPL offers. Writable so withheld Token-2022 fees can be
+    /// harvested from the escrow.
+    #[account(
+        mut,
+        constraint = token_mint_a.key() == interchain_origin_sol_offer.token_mint_a @ P2PError::InvalidTokenMint
+    )]
+    pub token_mint_a: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = seller_sol,
+        has_one = seller_sol @ P2PError::InvalidDepositor,
+        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+    /// CHECK: Per-offer native escrow. Only passed for native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", interchain_origin_sol_offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", interchain_origin_sol_offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(interchain_origin_sol_offer.is_native, interchain_origin_sol_offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Seller's token account receiving the reclaimed deposit. Only passed for
+    /// SPL offers.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = seller_sol,
+        token::token_program = token_program,
+    )]
+    pub seller_sol_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct HtlcLockNative<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// Wrapped SOL mint. Claim and refund require the HTLC's mint, so it must
+    /// be one the depositor cannot close.
+    #[account(address = native_mint::ID @ P2PError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + HtlcOffer::SIZE,
+        seeds = [b"htlc", depositor.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub htlc: Account<'info, HtlcOffer>,
+
+    /// CHECK: Per-offer native escrow, a zero-data system account so the
+    /// system transfer instruction does not error.
+    #[account(
+        init,
+        payer = depositor,
+        space = 0,
+        seeds = [b"escrow-native", htlc.key().as_ref()],
+        bump,
+        owner = system_program::ID
+    )]
+    pub escrow_native: AccountInfo<'info>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", native_mint::ID.as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct HtlcLockSpl<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account()]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + HtlcOffer::SIZE,
+        seeds = [b"htlc", depositor.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub htlc: Account<'info, HtlcOffer>,
+
+    /// Per-offer token escrow. It is its own authority, so no other offer's
+    /// signer can move these tokens.
+    #[account(
+        init,
+        payer = depositor,
+        seeds = [b"escrow", htlc.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_spl,
+        token::token_program = token_program,
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct HtlcClaim<'info> {
+    /// Anyone holding the preimage may submit it; pays for missing token accounts.
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// Receives the HTLC account rent once it is closed.
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+
+    /// Writable so withheld Token-2022 fees can be harvested from the escrow.
+    #[account(mut)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    // Closed by the handler after the claim event is emitted.
+    #[account(
+        mut,
+        has_one = depositor @ P2PError::InvalidDepositor,
+        has_one = recipient @ P2PError::InvalidRecipient,
+        has_one = token_mint @ P2PError::InvalidTokenMint,
+        seeds = [b"htlc", depositor.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, HtlcOffer>,
+
+    /// CHECK: Per-offer native escrow. Only passed for native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", htlc.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", htlc.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(htlc.is_native, htlc.token_mint) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Only passed for SPL locks.
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = token_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Protocol treasury for native SOL fees, a zero-data system account
+    /// like `vault-native`.
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = 0,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Treasury token account collecting SPL fees for `token_mint`. Only
+    /// passed for SPL locks.
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct HtlcRefund<'info> {
+    /// Anyone may crank an expired HTLC; they only pay the transaction fee.
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    /// Writable so withheld Token-2022 fees can be harvested from the escrow.
+    #[account(mut)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor @ P2PError::InvalidDepositor,
+        has_one = token_mint @ P2PError::InvalidTokenMint,
+        seeds = [b"htlc", depositor.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, HtlcOffer>,
+
+    /// CHECK: Per-offer native escrow. Only passed for native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", htlc.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", htlc.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(htlc.is_native, htlc.token_mint) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Depositor's token account receiving the refunded lock. Only passed for
+    /// SPL locks.
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, external_seller_evm: [u8; 20], external_seller_sol: Pubkey)]
+pub struct RelayOfferClone<'info> {
+    /// Registered relayer attesting the EVM-side deposit; pays for the offer account.
+    #[account(
+        mut,
+        constraint = relayer_registry.is_relayer(&relayer.key()) @ P2PError::UnauthorizedRelayer
+    )]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        seeds = [b"relayer-registry"],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    // Seeded by the seller rather than the relayer, matching the buyer deposit
+    // and finalize contexts.
+    #[account(
+        init,
+        payer = relayer,
+        // space: big enough for all fields
+        space = 8 + InterchainOffer::SIZE,
+        seeds = [b"InterChainoffer", external_seller_sol.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + TradeAttestation::SIZE,
+        seeds = [b"attestation", external_seller_sol.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, TradeAttestation>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, external_seller_evm: [u8; 20], external_seller_sol: Pubkey)]
+pub struct RelaySignedEvmTrade<'info> {
+    /// Anyone may submit; authenticity comes from the EVM signature.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"relayer-registry"],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + InterchainOffer::SIZE,
+        seeds = [b"InterChainoffer", external_seller_sol.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    /// Never closed; marks the trade id as consumed.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TradeAttestation::SIZE,
+        seeds = [b"attestation", external_seller_sol.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, TradeAttestation>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Secp256k1 instruction.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, external_seller_evm: [u8; 20], external_seller_sol: Pubkey)]
+pub struct AttestEvmTrade<'info> {
+    #[account(
+        constraint = relayer_registry.is_relayer(&relayer.key()) @ P2PError::UnauthorizedRelayer
+    )]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        seeds = [b"relayer-registry"],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: The trade's `InterchainOffer`, decoded in the handler. Closed
+    /// once the trade is finalized or refunded.
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", external_seller_sol.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_offer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"attestation", external_seller_sol.as_ref(), id.to_le_bytes().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, TradeAttestation>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct TakeInterchainOriginSolOffer<'info> {
+    #[account(mut)]
+    pub seller_sol: Signer<'info>, // seller origin on sol chain, who deposited the asset into contract
+
+    #[account(mut)]
+    pub external_buyer_sol: SystemAccount<'info>, // Buyer : UserB's solana address who is taking the offer
+
+    /// Only passed for SPL offers. Writable so withheld Token-2022 fees can be
+    /// harvested from the escrow.
+    #[account(
+        mut,
+        constraint = token_mint_a.key() == interchain_origin_sol_offer.token_mint_a @ P2PError::InvalidTokenMint
+    )]
+    pub token_mint_a: Option<InterfaceAccount<'info, Mint>>,
+
+    // #[account()]
+    // pub token_mint_b: Account<'info, Mint>,
+    #[account(
+        mut,
+        close = seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
+        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+    /// CHECK: Per-offer native escrow. Only passed for native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", interchain_origin_sol_offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", interchain_origin_sol_offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(interchain_origin_sol_offer.is_native, interchain_origin_sol_offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Taker's associated token account for receiving maker's SPL deposit.
+    /// Only passed for SPL offers.
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = external_buyer_sol,
+        associated_token::token_program = token_program,
+    )]
+    pub external_buyer_sol_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>, // token account of seller on sol chain where he received the asset
+
+    // #[account(
+    //     mut,
+    //     associated_token::mint = token_mint_b,
+    //     associated_token::authority = external_seller_sol,
+    // )]
+    // pub taker_token_account_b: Account<'info, TokenAccount>,
+
+    // #[account(
+    //     mut,
+    //     token::mint = token_mint_b,
+    //     token::authority = maker
+    // )]
+    // pub maker_token_account_b: Account<'info, TokenAccount>,
+    /// CHECK: Protocol treasury for native SOL fees, a zero-data system account
+    /// like `vault-native`.
+    #[account(
+        init_if_needed,
+        payer = seller_sol,
+        space = 0,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Treasury token account collecting SPL fees for `token_mint_a`. Only
+    /// passed for SPL offers.
+    #[account(
+        init_if_needed,
+        payer = seller_sol,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct TakeInterchainOffer<'info> {
+    #[account(mut)]
+    pub external_seller_sol: Signer<'info>, // Seller : UserA's solana address
+
+    #[account(mut)]
+    pub buyer_sol: SystemAccount<'info>, // Buyer : UserB's solana address
+
+    /// Writable so withheld Token-2022 fees can be harvested from the escrow.
+    #[account(mut)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    // #[account()]
+    // pub token_mint_b: Account<'info, Mint>,
+    #[account(
+        mut,
+        close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
+        has_one = buyer_sol @ P2PError::InvalidDepositor,
+        has_one = token_mint_a @ P2PError::InvalidTokenMint,
+        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, InterchainOffer>,
+    /// CHECK: Per-offer native escrow. Only passed for native offers.
+    #[account(
+        mut,
+        seeds = [b"escrow-native", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_native: Option<UncheckedAccount<'info>>,
+
+    /// Per-offer token escrow. Only passed for SPL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(offer.is_native, offer.deposited_mint) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Taker's associated token account for receiving maker's SPL deposit.
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = external_seller_sol,
+        associated_token::token_program = token_program,
+    )]
+    pub external_seller_sol_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    // #[account(
+    //     mut,
+    //     associated_token::mint = token_mint_b,
+    //     associated_token::authority = external_seller_sol,
+    // )]
+    // pub taker_token_account_b: Account<'info, TokenAccount>,
+
+    // #[account(
+    //     mut,
+    //     token::mint = token_mint_b,
+    //     token::authority = maker
+    // )]
+    // pub maker_token_account_b: Account<'info, TokenAccount>,
+    /// CHECK: Protocol treasury for native SOL fees, a zero-data system account
+    /// like `vault-native`.
+    #[account(
+        init_if_needed,
+        payer = external_seller_sol,
+        space = 0,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Treasury token account collecting SPL fees for `token_mint_a`.
+    #[account(
+        init_if_needed,
+        payer = external_seller_sol,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct TakeOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Writable so withheld Token-2022 fees can be harvested from the escrow.
+    #[account(mut)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account()]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    // Closed by the handler once the last slice has been filled.
+    #[account(
+        mut,
+        has_one = token_mint_a @ P2PError::InvalidTokenMint,
+        has_one = token_mint_b @ P2PError::InvalidTokenMint,
+        seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+    /// Per-offer token escrow, holding wSOL for SOL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(offer.is_native, offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Taker's associated token account for receiving maker's deposit. Left out
+    /// to receive a wSOL deposit as lamports through `wsol_temp`.
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Temporary wSOL account, opened and closed by the handler when
+    /// token A is unwrapped to the taker.
+    #[account(
+        mut,
+        seeds = [b"wsol-temp", taker.key().as_ref()],
+        bump
+    )]
+    pub wsol_temp: Option<UncheckedAccount<'info>>,
+
+    /// Left out to pay a SOL leg from the taker's lamports.
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub taker_token_account_b: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Left out to pay a SOL leg to the maker's lamports.
+    #[account(
+        mut,
+        token::mint = token_mint_b,
+        token::authority = maker,
+        token::token_program = token_program_b,
+    )]
+    pub maker_token_account_b: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Protocol treasury, a zero-data system account like `vault-native`.
+    /// SOL offers pay their fee in wSOL to `treasury_token_account_a`.
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 0,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Treasury token account collecting SPL fees for `token_mint_a`.
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of `token_mint_b`, which may differ from token A's.
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(order: SignedOrder)]
+pub struct FillSignedOrder<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(address = order.maker @ P2PError::UnauthorizedMaker)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(address = order.token_mint_a @ P2PError::InvalidTokenMint)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(address = order.token_mint_b @ P2PError::InvalidTokenMint)]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    /// Maker's token A account, delegated to `global_authority`.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub maker_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_b,
+        token::authority = taker,
+        token::token_program = token_program_b,
+    )]
+    pub taker_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: This is a PDA used as the delegate for makers' token accounts.
+    #[account(
+        seeds = [b"global-authority"],
+        bump
+    )]
+    pub global_authority: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + NonceBitmap::SIZE,
+        seeds = [
+            b"nonces",
+            order.maker.as_ref(),
+            (order.nonce / NONCES_PER_PAGE).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub nonce_bitmap: Account<'info, NonceBitmap>,
+
+    /// CHECK: Protocol treasury, the authority of `treasury_token_account_a`.
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 0,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Treasury token account collecting SPL fees for `token_mint_a`.
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 instruction.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of `token_mint_b`, which may differ from token A's.
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelSignedOrderNonce<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + NonceBitmap::SIZE,
+        seeds = [
+            b"nonces",
+            maker.key().as_ref(),
+            (nonce / NONCES_PER_PAGE).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub nonce_bitmap: Account<'info, NonceBitmap>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CancelOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Writable so withheld Token-2022 fees can be harvested from the escrow.
+    #[account(mut)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    // Seeds are derived from the stored maker so that a foreign signer fails on
+    // `has_one` with a typed error instead of a seeds mismatch.
+    #[account(
+        mut,
+        close = maker,  // Rent goes back to the maker together with the escrow.
+        has_one = maker @ P2PError::UnauthorizedMaker,
+        has_one = token_mint_a @ P2PError::InvalidTokenMint,
+        seeds = [b"offer", offer.maker.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+    /// Per-offer token escrow, holding wSOL for SOL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(offer.is_native, offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Maker's token account receiving the refunded deposit. Left out to
+    /// receive a wSOL deposit as lamports.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct AmendOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = maker @ P2PError::UnauthorizedMaker,
+        has_one = token_mint_a @ P2PError::InvalidTokenMint,
+        seeds = [b"offer", offer.maker.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Per-offer token escrow, holding wSOL for SOL offers.
+    #[account(
+        mut,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this offer's mint.
+    #[account(
+        mut,
+        seeds = [b"vault-ledger", vault_ledger.mint.as_ref()],
+        bump = vault_ledger.bump,
+        constraint = vault_ledger.mint == ledger_mint(offer.is_native, offer.token_mint_a) @ P2PError::InvalidVaultLedger
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    /// Maker's token account funding a top-up or receiving a withdrawal. Left
+    /// out to use lamports for a wSOL offer.
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_a: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Temporary wSOL account, opened and closed by the handler when a
+    /// withdrawal is unwrapped to the maker.
+    #[account(
+        mut,
+        seeds = [b"wsol-temp", maker.key().as_ref()],
+        bump
+    )]
+    pub wsol_temp: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct SetAllowedTaker<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker @ P2PError::UnauthorizedMaker,
+        seeds = [b"offer", offer.maker.as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+/// Context for depositing raw SOL
+#[derive(Accounts)]
+#[instruction(id: Option<u64>)]
+pub struct MakeOfferNative<'info> {
+    /// Person who deposits the SOL
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Wrapped SOL mint; the deposit is escrowed as wSOL.
+    #[account(address = native_mint::ID @ P2PError::InvalidTokenMint)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account()]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    /// Hands out this maker's offer ids.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + TradeCounter::SIZE,
+        seeds = [b"trade-counter", maker.key().as_ref()],
+        bump
+    )]
+    pub trade_counter: Account<'info, TradeCounter>,
+
+    /// The Offer PDA storing trade details
+    #[account(
+        init,
+        payer = maker,
+        // space: big enough for all fields
+        space = 8 + Offer::SIZE,
+        seeds = [b"offer", maker.key().as_ref(), trade_counter.id_for(id).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Per-offer wSOL escrow. It is its own authority, like SPL escrows.
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump,
+        token::mint = token_mint_a,
+        token::authority = escrow_spl,
+        token::token_program = token_program,
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", native_mint::ID.as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for depositing raw SOL
+#[derive(Accounts)]
+#[instruction(id: Option<u64>)]
+pub struct InterchainOriginSolMakeOfferNative<'info> {
+    /// Person who deposits the SOL
+    #[account(mut)]
+    pub seller_sol: Signer<'info>,
+
+    /// Just pass in any valid mint for 'A' and 'B' if you like.  
+    /// Even if `is_native`, we’re not transferring SPL here.  
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    // #[account()]
+    // pub token_mint_b: Account<'info, Mint>,
+    /// The Offer PDA storing trade details
+    // #[account(
+    //     init,
+    //     payer = maker,
+    //     // space: big enough for all fields
+    //     space = 8 + Offer::SIZE,
+    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+    //     bump
+    // )]
+    // pub offer: Account<'info, Offer>,
+
+    // #[account(
+    //     mut,
+    //     // close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
+    //     seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+    //     bump
+    // )]
+    // pub offer: Account<'info, InterchainOffer>,
+
+    /// Hands out interchain trade ids, unique across sellers since the EVM
+    /// side mirrors them.
+    #[account(
+        init_if_needed,
+        payer = seller_sol,
+        space = 8 + TradeCounter::SIZE,
+        seeds = [b"trade-counter"],
+        bump
+    )]
+    pub trade_counter: Account<'info, TradeCounter>,
+
+    #[account(
+        init,
+        payer = seller_sol,
+        // space: big enough for all fields
+        space = 8 + InterchainOriginSOlOffer::SIZE,
+        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), trade_counter.id_for(id).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+
+    /// A simple system-owned vault account to store raw SOL
+    // #[account(
+    //     init_if_needed,
+    //     payer = maker,
+    //     space = 16, // if you only store the VaultAccount's bump or minimal data
+    //     seeds = [b"vault-native"],
+    //     bump
+    // )]
+    // pub vault: Account<'info, GlobalSolVault>,
+
+    /// CHECK: Per-offer native escrow, a zero-data system account so the
+    /// system transfer instruction does not error.
+    #[account(
+        init,
+        payer = seller_sol,
+        space = 0,
+        seeds = [b"escrow-native", interchain_origin_sol_offer.key().as_ref()],
+        bump,
+        owner = system_program::ID
+    )]
+    pub escrow_native: AccountInfo<'info>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = seller_sol,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", native_mint::ID.as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for depositing raw SOL
+#[derive(Accounts)]
+#[instruction(id: u64, external_seller_sol: Pubkey)]
+pub struct InterchainMakeOfferNative<'info> {
+    /// Person who deposits the SOL
+    #[account(mut)]
+    pub buyer_sol: Signer<'info>,
+
+    /// Just pass in any valid mint for 'A' and 'B' if you like.  
+    /// Even if `is_native`, we’re not transferring SPL here.  
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account()]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    /// The Offer PDA storing trade details
+    // #[account(
+    //     init,
+    //     payer = maker,
+    //     // space: big enough for all fields
+    //     space = 8 + Offer::SIZE,
+    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+    //     bump
+    // )]
+    // pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        // close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
+        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, InterchainOffer>,
+
+    /// A simple system-owned vault account to store raw SOL
+    // #[account(
     //     init_if_needed,
     //     payer = maker,
     //     space = 16, // if you only store the VaultAccount's bump or minimal data
@@ -1051,474 +5253,1563 @@ pub struct MakeOfferNative<'info> {
     // )]
     // pub vault: Account<'info, GlobalSolVault>,
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// The global native vault.
-    /// **Important:** This vault is a system account with ZERO data (space = 0)
-    /// so that the system transfer instruction does not error.
-    #[account(
-        init_if_needed,
-        payer = maker,
-        space = 0,
-        seeds = [b"vault-native"],
-        bump,
-        owner = system_program::ID
-    )]
-    pub vault: AccountInfo<'info>, // Program-owned account
+    /// CHECK: Per-offer native escrow, a zero-data system account so the
+    /// system transfer instruction does not error.
+    #[account(
+        init,
+        payer = buyer_sol,
+        space = 0,
+        seeds = [b"escrow-native", offer.key().as_ref()],
+        bump,
+        owner = system_program::ID
+    )]
+    pub escrow_native: AccountInfo<'info>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = buyer_sol,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", native_mint::ID.as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for depositing SPL tokens
+#[derive(Accounts)]
+#[instruction(id: Option<u64>)]
+pub struct MakeOfferSpl<'info> {
+    /// Person who deposits the SPL tokens
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The SPL mint for the tokens being offered
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    /// The other token (B) wanted
+    #[account()]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    /// The maker's ATA holding their tokens to be offered
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// Hands out this maker's offer ids.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + TradeCounter::SIZE,
+        seeds = [b"trade-counter", maker.key().as_ref()],
+        bump
+    )]
+    pub trade_counter: Account<'info, TradeCounter>,
+
+    /// The Offer PDA storing trade details
+    #[account(
+        init,
+        payer = maker,
+        // space: big enough for all fields
+        space = 8 + Offer::SIZE,
+        seeds = [b"offer", maker.key().as_ref(), trade_counter.id_for(id).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Per-offer token escrow. It is its own authority, so no other offer's
+    /// signer can move these tokens.
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump,
+        token::mint = token_mint_a,
+        token::authority = escrow_spl,
+        token::token_program = token_program,
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", token_mint_a.key().as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: Option<u64>)]
+pub struct InterchainOriginSolMakeOfferSpl<'info> {
+    /// Person who deposits the SPL tokens
+    #[account(mut)]
+    pub seller_sol: Signer<'info>,
+
+    /// The SPL mint for the tokens being offered
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    /// The other token (B) wanted
+    #[account()]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    /// The maker's ATA holding their tokens to be offered
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = seller_sol,
+        token::token_program = token_program,
+    )]
+    pub seller_sol_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// The Offer PDA storing trade details
+    // #[account(
+    //     init,
+    //     payer = maker,
+    //     // space: big enough for all fields
+    //     space = 8 + Offer::SIZE,
+    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+    //     bump
+    // )]
+    // pub offer: Account<'info, Offer>,
+
+    /// Hands out interchain trade ids, unique across sellers since the EVM
+    /// side mirrors them.
+    #[account(
+        init_if_needed,
+        payer = seller_sol,
+        space = 8 + TradeCounter::SIZE,
+        seeds = [b"trade-counter"],
+        bump
+    )]
+    pub trade_counter: Account<'info, TradeCounter>,
+
+    #[account(
+        init,
+        payer = seller_sol,
+        // space: big enough for all fields
+        space = 8 + InterchainOriginSOlOffer::SIZE,
+        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), trade_counter.id_for(id).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+
+    /// Per-offer token escrow. It is its own authority, so no other offer's
+    /// signer can move these tokens.
+    #[account(
+        init,
+        payer = seller_sol,
+        seeds = [b"escrow", interchain_origin_sol_offer.key().as_ref()],
+        bump,
+        token::mint = token_mint_a,
+        token::authority = escrow_spl,
+        token::token_program = token_program,
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = seller_sol,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", token_mint_a.key().as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, external_seller_sol: Pubkey)]
+pub struct InterchainMakeOfferSpl<'info> {
+    /// Person who deposits the SPL tokens
+    #[account(mut)]
+    pub buyer_sol: Signer<'info>,
+
+    /// The SPL mint for the tokens being offered
+    #[account()]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    /// The other token (B) wanted
+    #[account()]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    /// The maker's ATA holding their tokens to be offered
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = buyer_sol,
+        token::token_program = token_program,
+    )]
+    pub buyer_sol_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// The Offer PDA storing trade details
+    // #[account(
+    //     init,
+    //     payer = maker,
+    //     // space: big enough for all fields
+    //     space = 8 + Offer::SIZE,
+    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+    //     bump
+    // )]
+    // pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        // close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
+        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, InterchainOffer>,
+
+    /// Per-offer token escrow. It is its own authority, so no other offer's
+    /// signer can move these tokens.
+    #[account(
+        init,
+        payer = buyer_sol,
+        seeds = [b"escrow", offer.key().as_ref()],
+        bump,
+        token::mint = token_mint_a,
+        token::authority = escrow_spl,
+        token::token_program = token_program,
+    )]
+    pub escrow_spl: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow owed across all offers of this mint.
+    #[account(
+        init_if_needed,
+        payer = buyer_sol,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault-ledger", token_mint_a.key().as_ref()],
+        bump
+    )]
+    pub vault_ledger: Account<'info, VaultLedger>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+// /// Single global SOL vault data.
+// #[account]
+// pub struct GlobalSolVault {
+//     pub bump: u8, // Could store more fields if you want
+// }
+
+/// Offer data.
+#[account]
+pub struct Offer {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_native: bool,
+    pub is_taker_native: bool,
+    pub status: OfferStatus,
+    pub expires_at: i64,
+    pub remaining_a: u64,              // token A still escrowed for takers
+    pub remaining_b: u64,              // token B still owed for `remaining_a`
+    pub fee_collected: u64,            // protocol fee taken from token A so far
+    pub allowed_taker: Option<Pubkey>, // only signer that may fill, if set
+    pub bump: u8,
+}
+
+impl Offer {
+    /// Helper for sizing. Adjust if you add more fields.
+    pub const SIZE: usize = 8   // id
+        + 32                    // maker
+        + 32                    // token_mint_a
+        + 32                    // token_mint_b
+        + 8                     // token_a_offered_amount
+        + 8                     // token_b_wanted_amount
+        + 1                     // is_native
+        + 1                     // is_taker_native
+        + 1                     // status
+        + 8                     // expires_at
+        + 8                     // remaining_a
+        + 8                     // remaining_b
+        + 8                     // fee_collected
+        + 1 + 32                // allowed_taker
+        + 1; // bump
+
+    /// Token B owed by a taker filling `fill_amount` of the remaining token A.
+    /// Rounds up so the maker never receives less than their asking price; the
+    /// last slice simply pays whatever is left of `remaining_b`.
+    pub fn token_b_owed(&self, fill_amount: u64) -> Result<u64> {
+        if fill_amount == self.remaining_a {
+            return Ok(self.remaining_b);
+        }
 
-    pub system_program: Program<'info, System>,
+        let numerator = (fill_amount as u128)
+            .checked_mul(self.remaining_b as u128)
+            .ok_or(P2PError::MathOverflow)?;
+        let owed = numerator.div_ceil(self.remaining_a as u128);
+
+        Ok(owed as u64)
+    }
 }
 
-/// Context for depositing raw SOL
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct InterchainOriginSolMakeOfferNative<'info> {
-    /// Person who deposits the SOL
-    #[account(mut)]
-    pub seller_sol: Signer<'info>,
+/// Lifecycle of `Offer`, `InterchainOffer` and `InterchainOriginSOlOffer`.
+/// `Completed`, `Cancelled` and `Expired` are terminal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OfferStatus {
+    /// Origin-EVM trade relayed, no buyer deposit yet.
+    Relayed,
+    /// Escrow holds the full offered amount.
+    Funded,
+    /// Part of an intrachain offer has been taken.
+    PartiallyFilled,
+    Completed,
+    Cancelled,
+    /// Refunded after `expires_at`.
+    Expired,
+    /// Relayers attested to conflicting trade data; only an expiry refund remains.
+    Disputed,
+}
 
-    /// Just pass in any valid mint for 'A' and 'B' if you like.  
-    /// Even if `is_native`, we’re not transferring SPL here.  
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+impl OfferStatus {
+    /// Escrow is still held for takers.
+    pub fn is_open(&self) -> bool {
+        matches!(self, OfferStatus::Funded | OfferStatus::PartiallyFilled)
+    }
 
-    // #[account()]
-    // pub token_mint_b: Account<'info, Mint>,
-    /// The Offer PDA storing trade details
-    // #[account(
-    //     init,
-    //     payer = maker,
-    //     // space: big enough for all fields
-    //     space = 8 + Offer::SIZE,
-    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-    //     bump
-    // )]
-    // pub offer: Account<'info, Offer>,
+    pub fn can_transition_to(&self, next: OfferStatus) -> bool {
+        use OfferStatus::*;
+        matches!(
+            (self, next),
+            (Relayed, Funded | Expired | Disputed)
+                | (
+                    Funded,
+                    PartiallyFilled | Completed | Cancelled | Expired | Disputed
+                )
+                | (
+                    PartiallyFilled,
+                    PartiallyFilled | Completed | Cancelled | Expired
+                )
+                | (Disputed, Expired)
+        )
+    }
 
-    // #[account(
-    //     mut,
-    //     // close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
-    //     seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-    //     bump
-    // )]
-    // pub offer: Account<'info, InterchainOffer>,
+    pub fn transition(&mut self, next: OfferStatus) -> Result<()> {
+        require!(
+            self.can_transition_to(next),
+            P2PError::InvalidStateTransition
+        );
+        *self = next;
+        Ok(())
+    }
+}
 
-    #[account(
-        init,
-        payer = seller_sol,
-        // space: big enough for all fields
-        space = 8 + InterchainOriginSOlOffer::SIZE,
-        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+/// Off-chain order signed by the maker and settled by `fill_signed_order`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedOrder {
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey, // mint the maker gives
+    pub token_mint_b: Pubkey, // mint the maker wants
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub expires_at: i64,
+    pub nonce: u64, // one-time, tracked in the maker's `NonceBitmap` pages
+}
 
-    /// A simple system-owned vault account to store raw SOL
-    // #[account(
-    //     init_if_needed,
-    //     payer = maker,
-    //     space = 16, // if you only store the VaultAccount's bump or minimal data
-    //     seeds = [b"vault-native"],
-    //     bump
-    // )]
-    // pub vault: Account<'info, GlobalSolVault>,
+/// Domain tag prefixed to every signed order message.
+pub const SIGNED_ORDER_DOMAIN: &[u8] = b"dhex-v1-order";
+
+impl SignedOrder {
+    /// Bytes the maker signs: domain tag, program id, then the Borsh-encoded
+    /// order, so signatures cannot be replayed against another deployment.
+    pub fn message(&self, program_id: &Pubkey) -> Result<Vec<u8>> {
+        let mut message = SIGNED_ORDER_DOMAIN.to_vec();
+        message.extend_from_slice(program_id.as_ref());
+        message.extend_from_slice(&self.try_to_vec()?);
+        Ok(message)
+    }
+}
+
+/// Nonces tracked by one `NonceBitmap` page.
+pub const NONCES_PER_PAGE: u64 = 256;
+
+/// Used signed-order nonces of one maker, a PDA at
+/// `seeds = [b"nonces", maker, page]` covering nonces
+/// `page * NONCES_PER_PAGE..(page + 1) * NONCES_PER_PAGE`.
+#[account]
+pub struct NonceBitmap {
+    pub maker: Pubkey,
+    pub page: u64,
+    pub bits: [u8; 32],
+    pub bump: u8,
+}
+
+impl NonceBitmap {
+    pub const SIZE: usize = 32  // maker
+        + 8                     // page
+        + 32                    // bits
+        + 1; // bump
+
+    /// Marks `nonce` as used, failing if it already was.
+    pub fn consume(&mut self, nonce: u64) -> Result<()> {
+        let bit = (nonce % NONCES_PER_PAGE) as usize;
+        let mask = 1u8 << (bit % 8);
+        require!(self.bits[bit / 8] & mask == 0, P2PError::NonceAlreadyUsed);
+        self.bits[bit / 8] |= mask;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct InterchainOriginSOlOffer {
+    //seller origin deposit fields
+    pub seller_sol: Pubkey,   // buyer : maker address on solana chain
+    pub seller_evm: [u8; 20], // EVM address of buyer
+
+    //relay fields
+    pub trade_id: u64,                // same as evm trade id
+    pub external_buyer_sol: Pubkey, // solana address of seller where he wants to receive the token
+    pub external_buyer_evm: [u8; 20], // EVM address of seller
+    pub is_seller_origin_sol: bool, // NO, seller origin is EVM
+    pub is_taker_native: bool,      // NO, taker wants spl token
+    pub status: OfferStatus,        // lifecycle, see `OfferStatus`
+    pub is_native: bool,            // NO, seller is not offering native token
+    pub chain_id: u64,
+
+    // deposit amount fields
+    pub token_a_offered_amount: u64, // seller offering 0.17 eth on evm chain
+    pub token_b_wanted_amount: u64,  // seller wants 15 USDC on solana chain
+
+    // buyer is transfering 15 USDC to seller which is spl-token
+    // we need token mint account address for USDC spl token
+    pub token_mint_a: Pubkey, // USDC mint account address
+    //pub buyer: Option<Pubkey>, // buyer address on solana chain
+    //pub buyer_token_account: Option<Pubkey>, // buyer token account address on solana chain
+    pub fee_collected: u64, // fee collected by the relayer
+
+    pub expires_at: i64, // unix timestamp after which the offer can no longer be filled
+    pub created_at: i64, // deposit time, start of the reclaim waiting period
+
+    pub bump: u8, // bump for the account
+}
+
+impl InterchainOriginSOlOffer {
+    pub const SIZE: usize = 32                    // seller_sol
+        + 20                    // seller_evm
+        + 8                     // trade_id
+        + 32                    // external_buyer_sol
+        + 20                    // external_buyer_evm
+        + 1                     // is_seller_origin_sol
+        + 1                     // is_taker_native
+        + 1                     // status
+        + 1                     // is_native
+        + 8                     // chain_id
+        + 8                     // token_a_offered_amount
+        + 8                     // token_b_wanted_amount
+        + 32                    // token_mint_a
+        + 8                     // fee_collected
+        + 8                     // expires_at
+        + 8                     // created_at
+        + 1; // bump
+}
+
+#[account]
+pub struct InterchainOffer {
+    //buyer deposit fields
+    pub buyer_sol: Pubkey,   // buyer : maker address on solana chain
+    pub buyer_evm: [u8; 20], // EVM address of buyer
+
+    //relay fields
+    pub trade_id: u64,                 // same as evm trade id
+    pub external_seller_sol: Pubkey, // solana address of seller where he wants to receive the token
+    pub external_seller_evm: [u8; 20], // EVM address of seller
+    pub is_seller_origin_sol: bool,  // NO, seller origin is EVM
+    pub is_taker_native: bool,       // NO, taker wants spl token
+    pub status: OfferStatus,         // lifecycle, see `OfferStatus`
+    pub is_native: bool,             // NO, seller is not offering native token
+    pub chain_id: u64,
+
+    // deposit amount fields
+    pub token_a_offered_amount: u64, // seller offering 0.17 eth on evm chain
+    pub token_b_wanted_amount: u64,  // seller wants 15 USDC on solana chain
+
+    // buyer is transfering 15 USDC to seller which is spl-token
+    // we need token mint account address for USDC spl token
+    pub token_mint_a: Pubkey, // USDC mint account address
+    //pub buyer: Option<Pubkey>, // buyer address on solana chain
+    //pub buyer_token_account: Option<Pubkey>, // buyer token account address on solana chain
+    pub fee_collected: u64, // fee collected by the relayer
+
+    pub relayer: Pubkey, // registered relayer that attested the EVM-side deposit
+    pub attestation_count: u8, // matching relayer attestations so far
+    pub required_attestations: u8, // registry threshold when the trade was relayed
+    pub evm_signer: [u8; 20], // EVM signer of a `relay_signed_evm_trade` payload, zero otherwise
+
+    // buyer deposit, set once by `interchain_origin_evm_deposit_seller_*`
+    pub deposited_amount: u64,  // amount the buyer actually escrowed
+    pub deposited_mint: Pubkey, // escrowed mint, default for native SOL
+
+    pub expires_at: i64, // unix timestamp after which the offer can no longer be filled
+
+    pub bump: u8, // bump for the account
+}
+
+impl InterchainOffer {
+    pub const SIZE: usize = 32                    // buyer_sol
+        + 20                    // buyer_evm
+        + 8                     // trade_id
+        + 32                    // external_seller_sol
+        + 20                    // external_seller_evm
+        + 1                     // is_seller_origin_sol
+        + 1                     // is_taker_native
+        + 1                     // status
+        + 1                     // is_native
+        + 8                     // chain_id
+        + 8                     // token_a_offered_amount
+        + 8                     // token_b_wanted_amount
+        + 32                    // token_mint_a
+        + 8                     // fee_collected
+        + 32                    // relayer
+        + 1                     // attestation_count
+        + 1                     // required_attestations
+        + 20                    // evm_signer
+        + 8                     // deposited_amount
+        + 32                    // deposited_mint
+        + 8                     // expires_at
+        + 1; // bump
+}
+
+/// Hash function behind an HTLC hashlock, matching what the EVM-side HTLC uses.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashlockKind {
+    Sha256,
+    Keccak256,
+}
+
+impl HashlockKind {
+    pub fn hash(&self, preimage: &[u8]) -> [u8; 32] {
+        match self {
+            HashlockKind::Sha256 => anchor_lang::solana_program::hash::hash(preimage).to_bytes(),
+            HashlockKind::Keccak256 => {
+                anchor_lang::solana_program::keccak::hash(preimage).to_bytes()
+            }
+        }
+    }
+}
+
+/// Hash time-locked deposit, a PDA at `seeds = [b"htlc", depositor, id]`.
+#[account]
+pub struct HtlcOffer {
+    pub id: u64,
+    pub depositor: Pubkey,
+    pub recipient: Pubkey,          // receives the funds on a valid claim
+    pub counterparty_evm: [u8; 20], // recipient's address on the EVM side, informational
+    pub token_mint: Pubkey,
+    pub is_native: bool,
+    pub amount: u64,
+    pub fee: u64, // protocol fee taken from `amount` on claim, fixed at lock time
+    pub hashlock: [u8; 32],
+    pub hash_kind: HashlockKind,
+    pub timelock: i64, // unix timestamp from which the depositor can reclaim
+    pub bump: u8,
+}
+
+impl HtlcOffer {
+    pub const SIZE: usize = 8   // id
+        + 32                    // depositor
+        + 32                    // recipient
+        + 20                    // counterparty_evm
+        + 32                    // token_mint
+        + 1                     // is_native
+        + 8                     // amount
+        + 8                     // fee
+        + 32                    // hashlock
+        + 1                     // hash_kind
+        + 8                     // timelock
+        + 1; // bump
+}
+
+/// Escrow owed to depositors for one mint, a PDA at
+/// `seeds = [b"vault-ledger", mint]`. Native SOL is keyed by the wrapped SOL mint.
+#[account]
+pub struct VaultLedger {
+    pub mint: Pubkey,
+    pub outstanding: u64,         // sum of every open escrow of `mint`
+    pub legacy_outstanding: u64,  // most the legacy shared vault can still owe
+    pub legacy_snapshotted: bool, // `legacy_outstanding` has been recorded
+    pub bump: u8,
+}
+
+impl VaultLedger {
+    pub const SIZE: usize = 32 // mint
+        + 8                    // outstanding
+        + 8                    // legacy_outstanding
+        + 1                    // legacy_snapshotted
+        + 1; // bump
+
+    pub fn credit(&mut self, mint: Pubkey, bump: u8, amount: u64) -> Result<()> {
+        // First deposit of this mint.
+        if self.mint == Pubkey::default() {
+            self.mint = mint;
+            self.bump = bump;
+        }
+        self.outstanding = self
+            .outstanding
+            .checked_add(amount)
+            .ok_or(P2PError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn debit(&mut self, amount: u64) -> Result<()> {
+        self.outstanding = self
+            .outstanding
+            .checked_sub(amount)
+            .ok_or(P2PError::PayoutExceedsLedger)?;
+        Ok(())
+    }
+
+    /// Records what the legacy shared vault of `mint` held when this program
+    /// first looked at it. New deposits go to per-offer escrows, so legacy
+    /// offers can be owed at most that much; anything arriving later is surplus.
+    pub fn snapshot_legacy_vault(&mut self, vault_balance: u64) {
+        if !self.legacy_snapshotted {
+            self.legacy_outstanding = vault_balance;
+            self.legacy_snapshotted = true;
+        }
+    }
+
+    pub fn debit_legacy(&mut self, amount: u64) -> Result<()> {
+        self.legacy_outstanding = self
+            .legacy_outstanding
+            .checked_sub(amount)
+            .ok_or(P2PError::PayoutExceedsLedger)?;
+        Ok(())
+    }
+}
+
+/// `Offer` as laid out before per-offer escrows, when deposits went to the
+/// shared `vault-native` and `global-authority` vaults.
+#[derive(AnchorDeserialize)]
+pub struct LegacyOffer {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_native: bool,
+    pub is_taker_native: bool,
+    pub is_swap_completed: bool,
+    pub bump: u8,
+}
+
+impl LegacyOffer {
+    pub const SIZE: usize = 8   // id
+        + 32                    // maker
+        + 32                    // token_mint_a
+        + 32                    // token_mint_b
+        + 8                     // token_a_offered_amount
+        + 8                     // token_b_wanted_amount
+        + 1                     // is_native
+        + 1                     // is_taker_native
+        + 1                     // is_swap_completed
+        + 1; // bump
+}
+
+/// `InterchainOffer` and `InterchainOriginSOlOffer` as laid out before
+/// per-offer escrows; both used the same fields.
+#[derive(AnchorDeserialize)]
+pub struct LegacyInterchainOffer {
+    pub depositor_sol: Pubkey, // `buyer_sol` or `seller_sol`
+    pub depositor_evm: [u8; 20],
+    pub trade_id: u64,
+    pub counterparty_sol: Pubkey,
+    pub counterparty_evm: [u8; 20],
+    pub is_seller_origin_sol: bool,
+    pub is_taker_native: bool,
+    pub is_swap_completed: bool,
+    pub is_native: bool,
+    pub chain_id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub token_mint_a: Pubkey, // never set for origin-SOL deposits
+    pub fee_collected: u64,
+    pub bump: u8,
+}
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// The global native vault.
-    /// **Important:** This vault is a system account with ZERO data (space = 0)
-    /// so that the system transfer instruction does not error.
-    #[account(
-        init_if_needed,
-        payer = seller_sol,
-        space = 0,
-        seeds = [b"vault-native"],
-        bump,
-        owner = system_program::ID
-    )]
-    pub vault: AccountInfo<'info>, // Program-owned account
+impl LegacyInterchainOffer {
+    /// Allocated size, which counted `trade_id` as 32 bytes.
+    pub const SIZE: usize = 32                    // depositor_sol
+        + 20                    // depositor_evm
+        + 32                    // trade_id
+        + 32                    // counterparty_sol
+        + 20                    // counterparty_evm
+        + 1                     // is_seller_origin_sol
+        + 1                     // is_taker_native
+        + 1                     // is_swap_completed
+        + 1                     // is_native
+        + 8                     // chain_id
+        + 8                     // token_a_offered_amount
+        + 8                     // token_b_wanted_amount
+        + 32                    // token_mint_a
+        + 8; // fee_collected
+}
 
-    pub system_program: Program<'info, System>,
+/// What the depositor of a legacy offer is owed from the shared vaults. Loads
+/// an open offer account of this program that still has a legacy layout,
+/// telling the three offer types apart by discriminator. Read-only: the
+/// account is only ever closed.
+#[derive(Clone)]
+pub struct LegacyEscrow {
+    pub depositor: Pubkey,
+    pub is_native: bool,
+    pub token_mint: Pubkey, // wrapped SOL mint for native deposits
+    pub amount: u64,
 }
 
-/// Context for depositing raw SOL
-#[derive(Accounts)]
-#[instruction(id: u64, external_seller_sol: Pubkey)]
-pub struct InterchainMakeOfferNative<'info> {
-    /// Person who deposits the SOL
-    #[account(mut)]
-    pub buyer_sol: Signer<'info>,
+impl AccountDeserialize for LegacyEscrow {
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let data: &[u8] = buf;
+        require!(data.len() > 8, P2PError::InvalidLegacyOffer);
+        let (discriminator, mut body) = data.split_at(8);
+
+        if data.len() == 8 + LegacyOffer::SIZE && discriminator == Offer::DISCRIMINATOR {
+            let offer = LegacyOffer::deserialize(&mut body)?;
+            require!(!offer.is_swap_completed, P2PError::SwapAlreadyCompleted);
+            return Ok(LegacyEscrow {
+                depositor: offer.maker,
+                is_native: offer.is_native,
+                token_mint: ledger_mint(offer.is_native, offer.token_mint_a),
+                amount: offer.token_a_offered_amount,
+            });
+        }
 
-    /// Just pass in any valid mint for 'A' and 'B' if you like.  
-    /// Even if `is_native`, we’re not transferring SPL here.  
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+        require!(
+            data.len() == 8 + LegacyInterchainOffer::SIZE,
+            P2PError::InvalidLegacyOffer
+        );
+        let offer = if discriminator == InterchainOriginSOlOffer::DISCRIMINATOR {
+            LegacyInterchainOffer::deserialize(&mut body)?
+        } else if discriminator == InterchainOffer::DISCRIMINATOR {
+            // Origin-EVM buyer deposits were never recorded.
+            return err!(P2PError::UnrecordedLegacyDeposit);
+        } else {
+            return err!(P2PError::InvalidLegacyOffer);
+        };
+        require!(!offer.is_swap_completed, P2PError::SwapAlreadyCompleted);
+        // Nor was the mint of origin-SOL token deposits.
+        require!(
+            offer.is_native || offer.token_mint_a != Pubkey::default(),
+            P2PError::UnrecordedLegacyDeposit
+        );
 
-    #[account()]
-    pub token_mint_b: Account<'info, Mint>,
+        Ok(LegacyEscrow {
+            depositor: offer.depositor_sol,
+            is_native: offer.is_native,
+            token_mint: ledger_mint(offer.is_native, offer.token_mint_a),
+            amount: offer.token_a_offered_amount,
+        })
+    }
+}
 
-    /// The Offer PDA storing trade details
-    // #[account(
-    //     init,
-    //     payer = maker,
-    //     // space: big enough for all fields
-    //     space = 8 + Offer::SIZE,
-    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-    //     bump
-    // )]
-    // pub offer: Account<'info, Offer>,
+impl AccountSerialize for LegacyEscrow {}
 
-    #[account(
-        mut,
-        // close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
-        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub offer: Account<'info, InterchainOffer>,
+impl Owner for LegacyEscrow {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
 
-    /// A simple system-owned vault account to store raw SOL
-    // #[account(
-    //     init_if_needed,
-    //     payer = maker,
-    //     space = 16, // if you only store the VaultAccount's bump or minimal data
-    //     seeds = [b"vault-native"],
-    //     bump
-    // )]
-    // pub vault: Account<'info, GlobalSolVault>,
+#[cfg(feature = "idl-build")]
+impl anchor_lang::IdlBuild for LegacyEscrow {}
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// The global native vault.
-    /// **Important:** This vault is a system account with ZERO data (space = 0)
-    /// so that the system transfer instruction does not error.
-    #[account(
-        init_if_needed,
-        payer = buyer_sol,
-        space = 0,
-        seeds = [b"vault-native"],
-        bump,
-        owner = system_program::ID
-    )]
-    pub vault: AccountInfo<'info>, // Program-owned account
+#[cfg(feature = "idl-build")]
+impl Discriminator for LegacyEscrow {
+    const DISCRIMINATOR: [u8; 8] = Offer::DISCRIMINATOR;
+}
 
-    pub system_program: Program<'info, System>,
+/// Bit set on every id a `TradeCounter` hands out, keeping counter ids apart
+/// from explicit ones.
+pub const COUNTER_TRADE_ID_FLAG: u64 = 1 << 63;
+
+/// Id allocator for new trades, a PDA at `seeds = [b"trade-counter", maker]`
+/// for intrachain offers and `seeds = [b"trade-counter"]` for origin-SOL
+/// interchain offers. Explicit ids, e.g. mirrored EVM trade ids, are used as
+/// given and never touch the counter; counter ids carry
+/// `COUNTER_TRADE_ID_FLAG`, so the two only meet if a caller picks a flagged id.
+#[account]
+pub struct TradeCounter {
+    pub next_id: u64,
+    pub bump: u8,
 }
 
-/// Context for depositing SPL tokens
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct MakeOfferSpl<'info> {
-    /// Person who deposits the SPL tokens
-    #[account(mut)]
-    pub maker: Signer<'info>,
+impl TradeCounter {
+    pub const SIZE: usize = 8 // next_id
+        + 1; // bump
 
-    /// The SPL mint for the tokens being offered
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+    /// Id a deposit passing `id` will use.
+    pub fn id_for(&self, id: Option<u64>) -> u64 {
+        id.unwrap_or(COUNTER_TRADE_ID_FLAG | self.next_id)
+    }
 
-    /// The other token (B) wanted
-    #[account()]
-    pub token_mint_b: Account<'info, Mint>,
+    /// Resolves the deposit's id, consuming the next counter id if none was
+    /// passed.
+    pub fn assign(&mut self, id: Option<u64>, bump: u8) -> Result<u64> {
+        let assigned = self.id_for(id);
+        if id.is_none() {
+            self.next_id = self
+                .next_id
+                .checked_add(1)
+                .filter(|next| next & COUNTER_TRADE_ID_FLAG == 0)
+                .ok_or(P2PError::MathOverflow)?;
+        }
+        self.bump = bump;
+        Ok(assigned)
+    }
+}
 
-    /// The maker's ATA holding their tokens to be offered
-    #[account(
-        mut,
-        token::mint = token_mint_a,
-        token::authority = maker
-    )]
-    pub maker_token_account_a: Account<'info, TokenAccount>,
+/// Mint keying the `VaultLedger` of an escrow.
+pub fn ledger_mint(is_native: bool, mint: Pubkey) -> Pubkey {
+    if is_native {
+        native_mint::ID
+    } else {
+        mint
+    }
+}
 
-    /// The Offer PDA storing trade details
-    #[account(
-        init,
-        payer = maker,
-        // space: big enough for all fields
-        space = 8 + Offer::SIZE,
-        seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub offer: Account<'info, Offer>,
+/// Sums what the per-offer escrows of `mint`, passed as `(offer, escrow)` pairs,
+/// hold. Escrows of closed offers hold nothing, whatever was sent to them since.
+/// Returns the total and the number of escrows counted.
+pub fn escrow_holdings(
+    program_id: &Pubkey,
+    mint: Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<(u64, u32)> {
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+
+    let pairs = accounts.chunks_exact(2);
+    require!(pairs.remainder().is_empty(), P2PError::InvalidEscrow);
+
+    let mut checked: Vec<Pubkey> = Vec::with_capacity(pairs.len());
+    let mut total: u64 = 0;
+    for pair in pairs {
+        let (offer, escrow) = (&pair[0], &pair[1]);
+        require!(!checked.contains(escrow.key), P2PError::DuplicateEscrow);
+
+        // Intrachain SOL is escrowed as wSOL; other native escrows hold lamports.
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"escrow", offer.key.as_ref()], program_id);
+        let (expected_native, _) =
+            Pubkey::find_program_address(&[b"escrow-native", offer.key.as_ref()], program_id);
+        let is_native_escrow = mint == native_mint::ID && expected_native == escrow.key();
+        require!(
+            expected == escrow.key() || is_native_escrow,
+            P2PError::InvalidEscrow
+        );
 
-    #[account(
-        init_if_needed,
-        payer = maker,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = global_authority,
-        //associated_token::allow_owner_off_curve = true  // <--- allow PDA as authority
-    )]
-    pub vault_spl: Account<'info, TokenAccount>,
+        let offer_is_open = offer.owner == program_id && !offer.data_is_empty();
+        let held = if !offer_is_open {
+            0
+        } else if escrow.data_is_empty() {
+            // Only a native escrow holds lamports; a closed token escrow holds nothing.
+            if is_native_escrow {
+                escrow.lamports().saturating_sub(rent_exempt)
+            } else {
+                0
+            }
+        } else {
+            require!(
+                *escrow.owner == token::ID || *escrow.owner == token_2022::ID,
+                P2PError::InvalidEscrow
+            );
+            let account = TokenAccount::try_deserialize(&mut &escrow.data.borrow()[..])?;
+            require_keys_eq!(account.mint, mint, P2PError::InvalidEscrow);
+            account.amount
+        };
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// It does not need additional validation because it's derived using `seeds = [b"global-authority"]`.
-    #[account(
-        seeds = [b"global-authority"], // ✅ Fixed PDA seed
-        bump
-    )]
-    pub global_authority: AccountInfo<'info>,
+        total = total.checked_add(held).ok_or(P2PError::MathOverflow)?;
+        checked.push(escrow.key());
+    }
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    Ok((total, checked.len() as u32))
 }
 
-#[derive(Accounts)]
-#[instruction(id: u64)]
-pub struct InterchainOriginSolMakeOfferSpl<'info> {
-    /// Person who deposits the SPL tokens
-    #[account(mut)]
-    pub seller_sol: Signer<'info>,
+/// How long a relayed origin-EVM offer waits for a buyer deposit before it can
+/// be closed as expired.
+pub const RELAYED_OFFER_FUNDING_WINDOW: i64 = 7 * 24 * 60 * 60;
 
-    /// The SPL mint for the tokens being offered
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+/// Wait after an origin-SOL deposit before the seller can reclaim it alone.
+pub const ORIGIN_SOL_RECLAIM_DELAY: i64 = 24 * 60 * 60;
 
-    /// The other token (B) wanted
-    #[account()]
-    pub token_mint_b: Account<'info, Mint>,
+/// Shorter wait when a registered relayer co-signs that no EVM-side fill happened.
+pub const ORIGIN_SOL_COSIGNED_RECLAIM_DELAY: i64 = 60 * 60;
 
-    /// The maker's ATA holding their tokens to be offered
-    #[account(
-        mut,
-        token::mint = token_mint_a,
-        token::authority = seller_sol
-    )]
-    pub seller_sol_token_account_a: Account<'info, TokenAccount>,
+/// Layout version written to `Config::version`.
+pub const CONFIG_VERSION: u8 = 3;
 
-    /// The Offer PDA storing trade details
-    // #[account(
-    //     init,
-    //     payer = maker,
-    //     // space: big enough for all fields
-    //     space = 8 + Offer::SIZE,
-    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-    //     bump
-    // )]
-    // pub offer: Account<'info, Offer>,
+/// Upper bound for every `*_fee_bps` in `Config` (10%).
+pub const MAX_FEE_BPS: u16 = 1_000;
 
-    #[account(
-        init,
-        payer = seller_sol,
-        // space: big enough for all fields
-        space = 8 + InterchainOriginSOlOffer::SIZE,
-        seeds = [b"InterChainoffer", seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub interchain_origin_sol_offer: Account<'info, InterchainOriginSOlOffer>,
+/// Bits of `Config::paused`. Cancels and refunds are never paused so users can
+/// always get their escrow back, and neither are HTLC claims.
+pub const PAUSE_INTRACHAIN_DEPOSITS: u8 = 1 << 0;
+pub const PAUSE_INTERCHAIN_DEPOSITS: u8 = 1 << 1;
+pub const PAUSE_FINALIZATIONS: u8 = 1 << 2;
+pub const PAUSE_RELAYS: u8 = 1 << 3;
 
-    #[account(
-        init_if_needed,
-        payer = seller_sol,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = global_authority,
-        //associated_token::allow_owner_off_curve = true  // <--- allow PDA as authority
-    )]
-    pub vault_spl: Account<'info, TokenAccount>,
+/// Global program configuration, a singleton PDA at `seeds = [b"config"]`.
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>, // set by `propose_admin`, cleared by `accept_admin`
+    pub version: u8,
+    pub features: u64,    // feature toggles, one bit per feature
+    pub guardian: Pubkey, // may pause, but only the admin can unpause
+    pub paused: u8,       // `PAUSE_*` bits
+    // protocol fees in basis points, taken from the amount released from escrow
+    pub intrachain_fee_bps: u16,
+    pub interchain_origin_evm_fee_bps: u16,
+    pub interchain_origin_sol_fee_bps: u16,
+    pub bump: u8,
+}
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// It does not need additional validation because it's derived using `seeds = [b"global-authority"]`.
-    #[account(
-        seeds = [b"global-authority"], // ✅ Fixed PDA seed
-        bump
-    )]
-    pub global_authority: AccountInfo<'info>,
+impl Config {
+    pub const SIZE: usize = 32          // admin
+        + 1 + 32                        // pending_admin
+        + 1                             // version
+        + 8                             // features
+        + 32                            // guardian
+        + 1                             // paused
+        + 2 * 3                         // *_fee_bps
+        + 1; // bump
+
+    pub fn require_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.paused & flag == 0, P2PError::ProgramPaused);
+        Ok(())
+    }
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+/// Fee owed on `amount` at `bps` basis points, rounded down.
+pub fn fee_amount(amount: u64, bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(P2PError::MathOverflow)?
+        / 10_000;
+
+    Ok(fee as u64)
 }
 
-#[derive(Accounts)]
-#[instruction(id: u64, external_seller_sol: Pubkey)]
-pub struct InterchainMakeOfferSpl<'info> {
-    /// Person who deposits the SPL tokens
-    #[account(mut)]
-    pub buyer_sol: Signer<'info>,
+/// Sends everything left in a per-offer native escrow, i.e. its rent, to
+/// `receiver`. A zero-data system account with no lamports is gone.
+pub fn close_native_escrow<'info>(
+    escrow: &AccountInfo<'info>,
+    receiver: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let lamports = escrow.lamports();
+    if lamports == 0 {
+        return Ok(());
+    }
 
-    /// The SPL mint for the tokens being offered
-    #[account()]
-    pub token_mint_a: Account<'info, Mint>,
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: escrow.clone(),
+                to: receiver.clone(),
+            },
+            signer_seeds,
+        ),
+        lamports,
+    )
+}
 
-    /// The other token (B) wanted
-    #[account()]
-    pub token_mint_b: Account<'info, Mint>,
+/// Drains the per-offer escrow of a closed offer, e.g. tokens sent to it after
+/// its offer was settled, into the treasury and closes it. Returns the amount
+/// drained.
+pub fn drain_orphaned_escrow<'info>(
+    program_id: &Pubkey,
+    accounts: &SweepSurplus<'info>,
+    offer: &'info AccountInfo<'info>,
+    escrow: &'info AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    require!(
+        offer.owner != program_id || offer.data_is_empty(),
+        P2PError::EscrowInUse
+    );
+    let mint = accounts.token_mint.key();
+
+    let (token_escrow, token_escrow_bump) =
+        Pubkey::find_program_address(&[b"escrow", offer.key.as_ref()], program_id);
+    if escrow.key() == token_escrow {
+        let escrow_account = InterfaceAccount::<TokenAccount>::try_from(escrow)?;
+        require_keys_eq!(escrow_account.mint, mint, P2PError::InvalidEscrow);
+        // The escrow is its own authority.
+        let seeds: &[&[u8]] = &[b"escrow", offer.key.as_ref(), &[token_escrow_bump]];
+        let signer_seeds = [seeds];
+
+        let amount = escrow_account.amount;
+        if amount > 0 {
+            let treasury_token_account = accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(P2PError::MissingTokenAccount)?;
+            transfer_tokens(
+                &accounts.token_program,
+                escrow.clone(),
+                &accounts.token_mint,
+                treasury_token_account.to_account_info(),
+                escrow.clone(),
+                hook_accounts,
+                amount,
+                &signer_seeds,
+            )?;
+        }
 
-    /// The maker's ATA holding their tokens to be offered
-    #[account(
-        mut,
-        token::mint = token_mint_a,
-        token::authority = buyer_sol
-    )]
-    pub buyer_sol_token_account_a: Account<'info, TokenAccount>,
+        close_token_escrow(
+            &accounts.token_program,
+            &accounts.token_mint,
+            &escrow_account,
+            &accounts.treasury,
+            &signer_seeds,
+        )?;
+        return Ok(amount);
+    }
 
-    /// The Offer PDA storing trade details
-    // #[account(
-    //     init,
-    //     payer = maker,
-    //     // space: big enough for all fields
-    //     space = 8 + Offer::SIZE,
-    //     seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
-    //     bump
-    // )]
-    // pub offer: Account<'info, Offer>,
+    // Native escrows outside intrachain offers hold lamports.
+    let (native_escrow, native_escrow_bump) =
+        Pubkey::find_program_address(&[b"escrow-native", offer.key.as_ref()], program_id);
+    require!(
+        mint == native_mint::ID && escrow.key() == native_escrow,
+        P2PError::InvalidEscrow
+    );
+    let seeds: &[&[u8]] = &[b"escrow-native", offer.key.as_ref(), &[native_escrow_bump]];
+
+    let amount = escrow.lamports();
+    close_native_escrow(
+        escrow,
+        &accounts.treasury,
+        &accounts.system_program,
+        &[seeds],
+    )?;
+    Ok(amount)
+}
 
-    #[account(
-        mut,
-        // close = external_seller_sol,  // This tells Anchor to close the offer account and send its lamports to the maker.
-        seeds = [b"InterChainoffer", external_seller_sol.key().as_ref(), id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub offer: Account<'info, InterchainOffer>,
+/// Closes a per-offer token escrow, returning its rent to `receiver`. An escrow
+/// still holding tokens (e.g. an unsolicited transfer) is left open rather than
+/// blocking the payout.
+pub fn close_token_escrow<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    escrow: &InterfaceAccount<'info, TokenAccount>,
+    receiver: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let info = escrow.to_account_info();
+    let balance = TokenAccount::try_deserialize(&mut &info.data.borrow()[..])?.amount;
+    if balance > 0 {
+        return Ok(());
+    }
 
-    #[account(
-        init_if_needed,
-        payer = buyer_sol,
-        associated_token::mint = token_mint_a,
-        associated_token::authority = global_authority,
-        //associated_token::allow_owner_off_curve = true  // <--- allow PDA as authority
-    )]
-    pub vault_spl: Account<'info, TokenAccount>,
+    // Token-2022 refuses to close an account with withheld transfer fees; move
+    // them to the mint, where the fee authority collects them.
+    let withheld = if *info.owner == token_2022::ID {
+        let data = info.try_borrow_data()?;
+        let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+        state
+            .get_extension::<TransferFeeAmount>()
+            .map_or(0, |fees| u64::from(fees.withheld_amount))
+    } else {
+        0
+    };
+    if withheld > 0 {
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(
+                token_program.to_account_info(),
+                HarvestWithheldTokensToMint {
+                    token_program_id: token_program.to_account_info(),
+                    mint: mint.to_account_info(),
+                },
+            ),
+            vec![info.clone()],
+        )?;
+    }
 
-    /// CHECK: This is a PDA used as the authority for the global vault.
-    /// It does not need additional validation because it's derived using `seeds = [b"global-authority"]`.
-    #[account(
-        seeds = [b"global-authority"], // ✅ Fixed PDA seed
-        bump
-    )]
-    pub global_authority: AccountInfo<'info>,
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token_interface::CloseAccount {
+            account: info.clone(),
+            destination: receiver.clone(),
+            authority: info,
+        },
+        signer_seeds,
+    ))
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+/// `transfer_checked` through either token program. Accounts a Token-2022
+/// transfer hook needs are looked up in `extra_accounts`, the instruction's
+/// remaining accounts, and forwarded to the hook.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    extra_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint.to_account_info(),
+        to,
+        authority,
+        extra_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Wraps `amount` lamports of `payer` into the wSOL token account `to`.
+pub fn wrap_sol<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    payer: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer,
+                to: to.clone(),
+            },
+        ),
+        amount,
+    )?;
+
+    token_interface::sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        token_interface::SyncNative { account: to },
+    ))
+}
+
+/// Pays `amount` of wSOL held by `from`, a PDA that is its own authority, out to
+/// `recipient` as lamports. The tokens go through `temp`, a wSOL account that
+/// `recipient` funds and that is closed back to it, rent included, within the
+/// same instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn unwrap_sol<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    native_mint: &InterfaceAccount<'info, Mint>,
+    from: AccountInfo<'info>,
+    from_seeds: &[&[&[u8]]],
+    temp: &AccountInfo<'info>,
+    temp_seeds: &[&[u8]],
+    recipient: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let space = token::spl_token::state::Account::LEN;
+
+    // Lamports sent to the address beforehand only lower the top-up, so the
+    // account cannot be blocked from being opened.
+    let top_up = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(temp.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: recipient.clone(),
+                    to: temp.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Allocate {
+                account_to_allocate: temp.clone(),
+            },
+            &[temp_seeds],
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Assign {
+                account_to_assign: temp.clone(),
+            },
+            &[temp_seeds],
+        ),
+        token_program.key,
+    )?;
+
+    token_interface::initialize_account3(CpiContext::new(
+        token_program.to_account_info(),
+        token_interface::InitializeAccount3 {
+            account: temp.clone(),
+            mint: native_mint.to_account_info(),
+            authority: temp.clone(),
+        },
+    ))?;
+
+    transfer_tokens(
+        token_program,
+        from.clone(),
+        native_mint,
+        temp.clone(),
+        from,
+        &[],
+        amount,
+        from_seeds,
+    )?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token_interface::CloseAccount {
+            account: temp.clone(),
+            destination: recipient,
+            authority: temp.clone(),
+        },
+        &[temp_seeds],
+    ))
+}
+
+/// What the recipient of `amount` of `mint` is credited, after any Token-2022
+/// transfer fee for the current epoch.
+pub fn net_of_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let info = mint.to_account_info();
+    if *info.owner != token_2022::ID {
+        return Ok(amount);
+    }
+
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+    let fee = config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(P2PError::MathOverflow)?;
+
+    Ok(amount - fee)
 }
 
-// /// Single global SOL vault data.
-// #[account]
-// pub struct GlobalSolVault {
-//     pub bump: u8, // Could store more fields if you want
-// }
+/// Maximum number of relayers held by `RelayerRegistry`.
+pub const MAX_RELAYERS: usize = 16;
 
-/// Offer data.
+/// Maximum number of EVM signer addresses held by `RelayerRegistry`.
+pub const MAX_EVM_SIGNERS: usize = 8;
+
+/// Admin-managed allowlist of relayers, a singleton PDA at
+/// `seeds = [b"relayer-registry"]`.
 #[account]
-pub struct Offer {
-    pub id: u64,
-    pub maker: Pubkey,
-    pub token_mint_a: Pubkey,
-    pub token_mint_b: Pubkey,
-    pub token_a_offered_amount: u64,
-    pub token_b_wanted_amount: u64,
-    pub is_native: bool,
-    pub is_taker_native: bool,
-    pub is_swap_completed: bool,
+pub struct RelayerRegistry {
+    pub relayers: Vec<Pubkey>,
+    pub threshold: u8, // matching attestations needed per origin-EVM trade
+    pub evm_signers: Vec<[u8; 20]>, // EVM oracle keys accepted by `relay_signed_evm_trade`
     pub bump: u8,
 }
 
-impl Offer {
-    /// Helper for sizing. Adjust if you add more fields.
-    pub const SIZE: usize = 8   // id
-        + 32                    // maker
-        + 32                    // token_mint_a
-        + 32                    // token_mint_b
-        + 8                     // token_a_offered_amount
-        + 8                     // token_b_wanted_amount
-        + 1                     // is_native
-        + 1
-        + 1                     // is_swap_completed
-        + 1; // is_taker_nativ
+impl RelayerRegistry {
+    pub const SIZE: usize = 4 + 32 * MAX_RELAYERS // relayers
+        + 1                                       // threshold
+        + 4 + 20 * MAX_EVM_SIGNERS                // evm_signers
+        + 1; // bump
+
+    pub fn is_relayer(&self, key: &Pubkey) -> bool {
+        self.relayers.contains(key)
+    }
+
+    pub fn is_evm_signer(&self, address: &[u8; 20]) -> bool {
+        self.evm_signers.contains(address)
+    }
+
+    /// A registry that never had a threshold set behaves as single-relayer mode.
+    pub fn required_attestations(&self) -> u8 {
+        self.threshold.max(1)
+    }
 }
 
+/// Relayer votes on one origin-EVM trade, a PDA at
+/// `seeds = [b"attestation", external_seller_sol, id]`. It is never closed, so
+/// a trade id relayed once cannot be relayed again by either relay path.
 #[account]
-pub struct InterchainOriginSOlOffer {
-    //seller origin deposit fields
-    pub seller_sol: Pubkey,   // buyer : maker address on solana chain
-    pub seller_evm: [u8; 20], // EVM address of buyer
-
-    //relay fields
-    pub trade_id: u64,                // same as evm trade id
-    pub external_buyer_sol: Pubkey, // solana address of seller where he wants to receive the token
-    pub external_buyer_evm: [u8; 20], // EVM address of seller
-    pub is_seller_origin_sol: bool, // NO, seller origin is EVM
-    pub is_taker_native: bool,      // NO, taker wants spl token
-    pub is_swap_completed: bool,    // NO, swap is not completed
-    pub is_native: bool,            // NO, seller is not offering native token
-    pub chain_id: u64,
+pub struct TradeAttestation {
+    pub trade_id: u64,
+    pub digest: [u8; 32], // `evm_trade_digest` of the first (relaying) attestation
+    pub attesters: Vec<Attester>, // registered relayers that have attested, matching or not
+    pub required_attestations: u8, // registry threshold at relay time, zero for signed trades
+    pub bump: u8,
+}
 
-    // deposit amount fields
-    pub token_a_offered_amount: u64, // seller offering 0.17 eth on evm chain
-    pub token_b_wanted_amount: u64,  // seller wants 15 USDC on solana chain
+impl TradeAttestation {
+    pub const SIZE: usize = 8           // trade_id
+        + 32                            // digest
+        + 4 + Attester::SIZE * MAX_RELAYERS // attesters
+        + 1                             // required_attestations
+        + 1; // bump
 
-    // buyer is transfering 15 USDC to seller which is spl-token
-    // we need token mint account address for USDC spl token
-    pub token_mint_a: Pubkey, // USDC mint account address
-    //pub buyer: Option<Pubkey>, // buyer address on solana chain
-    //pub buyer_token_account: Option<Pubkey>, // buyer token account address on solana chain
-    pub fee_collected: u64, // fee collected by the relayer
+    /// Attestations matching `digest`.
+    pub fn matching(&self) -> u8 {
+        self.attesters.iter().filter(|a| a.matches).count() as u8
+    }
 
-    pub bump: u8, // bump for the account
+    /// Attestations whose digest did not match.
+    pub fn conflicts(&self) -> u8 {
+        self.attesters.iter().filter(|a| !a.matches).count() as u8
+    }
 }
 
-impl InterchainOriginSOlOffer {
-    pub const SIZE: usize = 32                    // buyer_sol
-        + 20                    // buyer_evm
-        + 32                    // trade_id
-        + 32                    // maker
-        + 20                    // external_seller
-        + 1                     // is_seller_origin_sol
-        + 1                     // is_taker_native
-        + 1                     // is_swap_completed
-        + 1                     // is_native
-        + 8                     // chain_id
-        + 8                     // token_a_offered_amount
-        + 8                     // token_b_wanted_amount
-        + 32                    // token_mint_a
-        + 8; // fee_collected
+/// One relayer's vote in a `TradeAttestation`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Attester {
+    pub relayer: Pubkey,
+    pub matches: bool, // submitted digest matched the relayed trade
 }
 
-#[account]
-pub struct InterchainOffer {
-    //buyer deposit fields
-    pub buyer_sol: Pubkey,   // buyer : maker address on solana chain
-    pub buyer_evm: [u8; 20], // EVM address of buyer
-
-    //relay fields
-    pub trade_id: u64,                 // same as evm trade id
-    pub external_seller_sol: Pubkey, // solana address of seller where he wants to receive the token
-    pub external_seller_evm: [u8; 20], // EVM address of seller
-    pub is_seller_origin_sol: bool,  // NO, seller origin is EVM
-    pub is_taker_native: bool,       // NO, taker wants spl token
-    pub is_swap_completed: bool,     // NO, swap is not completed
-    pub is_native: bool,             // NO, seller is not offering native token
-    pub chain_id: u64,
+impl Attester {
+    pub const SIZE: usize = 32 // relayer
+        + 1; // matches
+}
 
-    // deposit amount fields
-    pub token_a_offered_amount: u64, // seller offering 0.17 eth on evm chain
-    pub token_b_wanted_amount: u64,  // seller wants 15 USDC on solana chain
+/// Domain tag prefixed to every EVM trade message.
+pub const EVM_TRADE_DOMAIN: &[u8] = b"dhex-v1-evm-trade";
+
+/// Canonical encoding of the EVM `tradeCreated` fields, behind the domain tag
+/// and program id so it cannot be replayed against another deployment.
+/// Relayers attest to its SHA-256 and EVM signers sign it directly.
+#[allow(clippy::too_many_arguments)]
+pub fn evm_trade_message(
+    program_id: &Pubkey,
+    trade_id: u64,
+    chain_id: u64,
+    external_seller_evm: &[u8; 20],
+    external_seller_sol: &Pubkey,
+    token_a_offered_amount: u64,
+    token_b_wanted_amount: u64,
+    is_taker_native: bool,
+    token_mint_a: &Pubkey,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(EVM_TRADE_MESSAGE_LEN);
+    message.extend_from_slice(EVM_TRADE_DOMAIN);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(&trade_id.to_le_bytes());
+    message.extend_from_slice(&chain_id.to_le_bytes());
+    message.extend_from_slice(external_seller_evm);
+    message.extend_from_slice(external_seller_sol.as_ref());
+    message.extend_from_slice(&token_a_offered_amount.to_le_bytes());
+    message.extend_from_slice(&token_b_wanted_amount.to_le_bytes());
+    message.push(is_taker_native as u8);
+    message.extend_from_slice(token_mint_a.as_ref());
+    message
+}
 
-    // buyer is transfering 15 USDC to seller which is spl-token
-    // we need token mint account address for USDC spl token
-    pub token_mint_a: Pubkey, // USDC mint account address
-    //pub buyer: Option<Pubkey>, // buyer address on solana chain
-    //pub buyer_token_account: Option<Pubkey>, // buyer token account address on solana chain
-    pub fee_collected: u64, // fee collected by the relayer
+pub const EVM_TRADE_MESSAGE_LEN: usize =
+    EVM_TRADE_DOMAIN.len() + 32 + 8 + 8 + 20 + 32 + 8 + 8 + 1 + 32;
+
+/// SHA-256 of `evm_trade_message`.
+#[allow(clippy::too_many_arguments)]
+pub fn evm_trade_digest(
+    program_id: &Pubkey,
+    trade_id: u64,
+    chain_id: u64,
+    external_seller_evm: &[u8; 20],
+    external_seller_sol: &Pubkey,
+    token_a_offered_amount: u64,
+    token_b_wanted_amount: u64,
+    is_taker_native: bool,
+    token_mint_a: &Pubkey,
+) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hash(&evm_trade_message(
+        program_id,
+        trade_id,
+        chain_id,
+        external_seller_evm,
+        external_seller_sol,
+        token_a_offered_amount,
+        token_b_wanted_amount,
+        is_taker_native,
+        token_mint_a,
+    ))
+    .to_bytes()
+}
 
-    pub bump: u8, // bump for the account
+/// Size of one offsets entry in an Ed25519 precompile instruction.
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Checks that the instruction right before the current one is an Ed25519
+/// precompile instruction carrying exactly one signature by `signer` over
+/// `message`, with every offset pointing into that instruction itself.
+pub fn verify_ed25519_instruction(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
+
+    let current_index = instructions::load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, P2PError::InvalidEd25519Instruction);
+    let ed25519_index = current_index - 1;
+    let ix =
+        instructions::load_instruction_at_checked(ed25519_index as usize, instructions_sysvar)?;
+    require!(
+        ix.program_id == ed25519_program::ID,
+        P2PError::InvalidEd25519Instruction
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= 2 + ED25519_OFFSETS_LEN && data[0] == 1,
+        P2PError::InvalidEd25519Instruction
+    );
+    let offsets = &data[2..2 + ED25519_OFFSETS_LEN];
+    let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+    let signature_ix_index = read_u16(2);
+    let public_key_offset = read_u16(4) as usize;
+    let public_key_ix_index = read_u16(6);
+    let message_offset = read_u16(8) as usize;
+    let message_size = read_u16(10) as usize;
+    let message_ix_index = read_u16(12);
+
+    // `u16::MAX` means "this instruction"; anything else must name it explicitly.
+    let is_own = |index: u16| index == u16::MAX || index == ed25519_index;
+    require!(
+        is_own(signature_ix_index) && is_own(public_key_ix_index) && is_own(message_ix_index),
+        P2PError::InvalidEd25519Instruction
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(P2PError::InvalidEd25519Instruction)?;
+    require!(
+        public_key == signer.as_ref(),
+        P2PError::InvalidOrderSignature
+    );
+
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(P2PError::InvalidEd25519Instruction)?;
+    require!(signed_message == message, P2PError::InvalidOrderSignature);
+
+    Ok(())
 }
 
-impl InterchainOffer {
-    pub const SIZE: usize = 32                    // buyer_sol
-        + 20                    // buyer_evm
-        + 32                    // trade_id
-        + 32                    // maker
-        + 20                    // external_seller
-        + 1                     // is_seller_origin_sol
-        + 1                     // is_taker_native
-        + 1                     // is_swap_completed
-        + 1                     // is_native
-        + 8                     // chain_id
-        + 8                     // token_a_offered_amount
-        + 8                     // token_b_wanted_amount
-        + 32                    // token_mint_a
-        + 8; // fee_collected
+/// Size of one offsets entry in a Secp256k1 precompile instruction.
+const SECP256K1_OFFSETS_LEN: usize = 11;
+
+/// Checks that the instruction right before the current one is a Secp256k1
+/// precompile instruction carrying exactly one signature over `message`, with
+/// every offset pointing into that instruction itself. Returns the recovered
+/// EVM address; the runtime has already verified the signature.
+pub fn verify_secp256k1_instruction(
+    instructions_sysvar: &AccountInfo,
+    message: &[u8],
+) -> Result<[u8; 20]> {
+    use anchor_lang::solana_program::{secp256k1_program, sysvar::instructions};
+
+    let current_index = instructions::load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, P2PError::InvalidSecp256k1Instruction);
+    let secp_index = current_index - 1;
+    let ix = instructions::load_instruction_at_checked(secp_index as usize, instructions_sysvar)?;
+    require!(
+        ix.program_id == secp256k1_program::ID,
+        P2PError::InvalidSecp256k1Instruction
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() > SECP256K1_OFFSETS_LEN && data[0] == 1,
+        P2PError::InvalidSecp256k1Instruction
+    );
+    let offsets = &data[1..1 + SECP256K1_OFFSETS_LEN];
+    let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]) as usize;
+    let signature_ix_index = u16::from(offsets[2]);
+    let eth_address_offset = read_u16(3);
+    let eth_address_ix_index = u16::from(offsets[5]);
+    let message_offset = read_u16(6);
+    let message_size = read_u16(8);
+    let message_ix_index = u16::from(offsets[10]);
+
+    // Offsets pointing at another instruction could smuggle in a signature
+    // over data we never see. Compared untruncated, so a precompile past
+    // index 255 cannot alias an earlier instruction.
+    require!(
+        signature_ix_index == secp_index
+            && eth_address_ix_index == secp_index
+            && message_ix_index == secp_index,
+        P2PError::InvalidSecp256k1Instruction
+    );
+
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(P2PError::InvalidSecp256k1Instruction)?;
+    require!(signed_message == message, P2PError::EvmMessageMismatch);
+
+    let eth_address: [u8; 20] = data
+        .get(eth_address_offset..eth_address_offset + 20)
+        .ok_or(P2PError::InvalidSecp256k1Instruction)?
+        .try_into()
+        .map_err(|_| P2PError::InvalidSecp256k1Instruction)?;
+
+    Ok(eth_address)
 }
 
 /// Event emitted when a trade is created.
 #[event]
+#[derive(Clone, Debug)]
 pub struct CreateTradeEvent {
     #[index]
     pub id: u64,
@@ -1526,10 +6817,12 @@ pub struct CreateTradeEvent {
     pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
     pub is_taker_native: bool,
-    is_swap_completed: bool,
+    pub status: OfferStatus,
+    pub expires_at: i64,
 }
 
 #[event]
+#[derive(Clone, Debug)]
 pub struct InterchainOriginSolCreateTradeEvent {
     #[index]
     pub id: u64,
@@ -1539,10 +6832,12 @@ pub struct InterchainOriginSolCreateTradeEvent {
     pub token_b_wanted_amount: u64,
     pub is_taker_native: bool,
     pub is_native: bool,
-    is_swap_completed: bool,
+    pub status: OfferStatus,
+    pub expires_at: i64,
 }
 
 #[event]
+#[derive(Clone, Debug)]
 pub struct InterchainCreateTradeEvent {
     #[index]
     pub id: u64,
@@ -1550,11 +6845,13 @@ pub struct InterchainCreateTradeEvent {
     pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
     pub is_taker_native: bool,
-    is_swap_completed: bool,
+    pub status: OfferStatus,
+    pub expires_at: i64,
 }
 
 /// Event emitted when a trade is completed.
 #[event]
+#[derive(Clone, Debug)]
 pub struct InterchainSwapCompletedEvent {
     #[index]
     pub id: u64,
@@ -1562,10 +6859,31 @@ pub struct InterchainSwapCompletedEvent {
     pub seller: Pubkey,
     pub token_a_transferred: u64,
     pub token_b_transferred: u64,
-    pub is_swap_completed: bool,
+    pub fee_collected: u64,
+    pub amount_received: u64, // credited on Solana, net of fees
+    pub status: OfferStatus,
+}
+
+/// Event emitted for every slice taken from an intrachain offer.
+#[event]
+#[derive(Clone, Debug)]
+pub struct PartialFillEvent {
+    #[index]
+    pub id: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub token_a_filled: u64,
+    pub token_b_paid: u64,
+    pub token_a_received: u64, // credited to the taker, net of fees
+    pub token_b_received: u64, // credited to the maker, net of transfer fees
+    pub fee: u64,              // part of `token_a_filled` sent to the treasury
+    pub remaining_a: u64,
+    pub remaining_b: u64,
+    pub status: OfferStatus,
 }
 
 #[event]
+#[derive(Clone, Debug)]
 pub struct SwapCompletedEvent {
     #[index]
     pub id: u64,
@@ -1573,23 +6891,316 @@ pub struct SwapCompletedEvent {
     pub taker: Pubkey,
     pub token_a_transferred: u64,
     pub token_b_transferred: u64,
-    pub is_swap_completed: bool,
+    pub fee_collected: u64,
+    pub status: OfferStatus,
 }
 
+/// Event emitted when a maker cancels an open offer.
 #[event]
+#[derive(Clone, Debug)]
+pub struct OfferCancelledEvent {
+    #[index]
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_a_refunded: u64,
+    pub is_native: bool,
+    pub status: OfferStatus,
+}
+
+/// Event emitted when a maker re-terms an open offer.
+#[event]
+#[derive(Clone, Debug)]
+pub struct OfferAmendedEvent {
+    #[index]
+    pub id: u64,
+    pub maker: Pubkey,
+    pub old_remaining_a: u64,
+    pub new_remaining_a: u64,
+    pub old_remaining_b: u64,
+    pub new_remaining_b: u64,
+    pub old_expires_at: i64,
+    pub new_expires_at: i64,
+}
+
+/// Event emitted when a maker changes who may fill an offer.
+#[event]
+#[derive(Clone, Debug)]
+pub struct AllowedTakerSetEvent {
+    #[index]
+    pub id: u64,
+    pub maker: Pubkey,
+    pub allowed_taker: Option<Pubkey>,
+}
+
+/// Event emitted when an expired offer's escrow is returned to its depositor.
+#[event]
+#[derive(Clone, Debug)]
+pub struct ExpiredOfferRefundedEvent {
+    #[index]
+    pub id: u64,
+    pub depositor: Pubkey,
+    pub amount_refunded: u64,
+    pub is_native: bool,
+    pub status: OfferStatus,
+}
+
+#[event]
+#[derive(Clone, Debug)]
 pub struct RelayEvmTradeEvent {
     pub trade_id: u64,
     pub external_seller_sol: Pubkey,
     pub external_seller_evm: [u8; 20],
     pub is_seller_origin_sol: bool,
     pub is_taker_native: bool,
-    pub is_swap_completed: bool,
+    pub status: OfferStatus,
     pub is_native: bool,
     pub chain_id: u64,
     pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
     pub token_mint_a: Pubkey,
     pub fee_collected: u64,
+    pub relayer: Pubkey,
+    pub evm_signer: [u8; 20],
+}
+
+/// Event emitted when the config is created or its feature toggles change.
+#[event]
+#[derive(Clone, Debug)]
+pub struct ConfigUpdatedEvent {
+    pub admin: Pubkey,
+    pub version: u8,
+    pub features: u64,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct AdminProposedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct AdminTransferredEvent {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct GuardianUpdatedEvent {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+}
+
+/// Event emitted whenever the pause bits change.
+#[event]
+#[derive(Clone, Debug)]
+pub struct PauseUpdatedEvent {
+    pub authority: Pubkey,
+    pub paused: u8,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct FeesUpdatedEvent {
+    pub admin: Pubkey,
+    pub intrachain_fee_bps: u16,
+    pub interchain_origin_evm_fee_bps: u16,
+    pub interchain_origin_sol_fee_bps: u16,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct FeesWithdrawnEvent {
+    pub admin: Pubkey,
+    pub destination: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub is_native: bool,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct RelayerAddedEvent {
+    pub admin: Pubkey,
+    pub relayer: Pubkey,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct RelayerRemovedEvent {
+    pub admin: Pubkey,
+    pub relayer: Pubkey,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct RelayerThresholdUpdatedEvent {
+    pub admin: Pubkey,
+    pub threshold: u8,
+}
+
+/// Event emitted for every matching attestation, including the relaying one.
+#[event]
+#[derive(Clone, Debug)]
+pub struct TradeAttestedEvent {
+    pub trade_id: u64,
+    pub external_seller_sol: Pubkey,
+    pub relayer: Pubkey,
+    pub attestation_count: u8,
+    pub required_attestations: u8,
+}
+
+/// Event emitted when a relayer attests to different trade data than the
+/// relaying attestation.
+#[event]
+#[derive(Clone, Debug)]
+pub struct ConflictingAttestationEvent {
+    pub trade_id: u64,
+    pub external_seller_sol: Pubkey,
+    pub relayer: Pubkey,
+    pub expected_digest: [u8; 32],
+    pub submitted_digest: [u8; 32],
+    pub conflicts: u8,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct EvmSignerAddedEvent {
+    pub admin: Pubkey,
+    pub evm_signer: [u8; 20],
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct EvmSignerRemovedEvent {
+    pub admin: Pubkey,
+    pub evm_signer: [u8; 20],
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct SignedOrderFilledEvent {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub nonce: u64,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub token_a_received: u64, // credited to the taker, net of fees
+    pub token_b_received: u64, // credited to the maker, net of transfer fees
+    pub fee_collected: u64,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct SignedOrderNonceCancelledEvent {
+    pub maker: Pubkey,
+    pub nonce: u64,
+}
+
+/// Event emitted when the EVM buyer of an origin-SOL deposit is recorded, so
+/// the EVM side can reconcile the match.
+#[event]
+#[derive(Clone, Debug)]
+pub struct InterchainBuyerBoundEvent {
+    #[index]
+    pub id: u64,
+    pub seller_sol: Pubkey,
+    pub external_buyer_sol: Pubkey,
+    pub external_buyer_evm: [u8; 20],
+    pub chain_id: u64,
+    pub bound_by: Pubkey,
+}
+
+/// Event emitted when a seller reclaims an unfilled origin-SOL deposit.
+#[event]
+#[derive(Clone, Debug)]
+pub struct InterchainOfferReclaimedEvent {
+    #[index]
+    pub id: u64,
+    pub seller_sol: Pubkey,
+    pub amount_refunded: u64,
+    pub is_native: bool,
+    pub relayer: Option<Pubkey>, // co-signer, if the shorter delay was used
+    pub status: OfferStatus,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct HtlcLockedEvent {
+    #[index]
+    pub id: u64,
+    pub depositor: Pubkey,
+    pub recipient: Pubkey,
+    pub counterparty_evm: [u8; 20],
+    pub token_mint: Pubkey,
+    pub is_native: bool,
+    pub amount: u64,
+    pub fee: u64,
+    pub hashlock: [u8; 32],
+    pub hash_kind: HashlockKind,
+    pub timelock: i64,
+}
+
+/// Event emitted when an HTLC is claimed. `preimage` is the secret the
+/// counterparty needs to claim the EVM-side HTLC.
+#[event]
+#[derive(Clone, Debug)]
+pub struct HtlcClaimedEvent {
+    #[index]
+    pub id: u64,
+    pub depositor: Pubkey,
+    pub recipient: Pubkey,
+    pub hashlock: [u8; 32],
+    pub preimage: [u8; 32],
+    pub amount: u64,
+    pub fee_collected: u64,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct HtlcRefundedEvent {
+    #[index]
+    pub id: u64,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub is_native: bool,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct SolvencyReportEvent {
+    pub mint: Pubkey,
+    pub outstanding: u64,
+    pub observed: u64,
+    pub escrows_checked: u32,
+    pub solvent: bool,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct SurplusSweptEvent {
+    pub admin: Pubkey,
+    pub token_mint: Pubkey,
+    pub is_native: bool,
+    pub amount: u64,             // from the legacy shared vault
+    pub legacy_outstanding: u64, // held back in the shared vault for legacy offers
+    pub orphaned: u64,           // drained from the escrows of closed offers
+    pub escrows_closed: u8,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct LegacyOfferRefundedEvent {
+    pub caller: Pubkey,
+    pub offer: Pubkey,
+    pub depositor: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub is_native: bool,
 }
 
 #[error_code]
@@ -1600,4 +7211,112 @@ pub enum P2PError {
     SwapAlreadyCompleted,
     #[msg("Maker and taker cannot be the same.")]
     MakerAndTakerCannotBeSame,
+    #[msg("Only the maker of this offer can perform this action.")]
+    UnauthorizedMaker,
+    #[msg("Token mint does not match the offer.")]
+    InvalidTokenMint,
+    #[msg("Expiry must be in the future.")]
+    InvalidExpiry,
+    #[msg("Offer has expired.")]
+    OfferExpired,
+    #[msg("Offer has not expired yet.")]
+    OfferNotExpired,
+    #[msg("Offer has no deposit to refund.")]
+    NothingToRefund,
+    #[msg("Account is not the depositor of this offer.")]
+    InvalidDepositor,
+    #[msg("A token account is required unless the leg is wrapped SOL.")]
+    MissingTokenAccount,
+    #[msg("The offer's token mint was not provided.")]
+    MissingTokenMint,
+    #[msg("Fill amount must be non-zero and at most the remaining amount.")]
+    InvalidFillAmount,
+    #[msg("Arithmetic overflow.")]
+    MathOverflow,
+    #[msg("Only the admin can perform this action.")]
+    UnauthorizedAdmin,
+    #[msg("Signer is not the pending admin.")]
+    UnauthorizedPendingAdmin,
+    #[msg("This action is paused.")]
+    ProgramPaused,
+    #[msg("Only the admin can unpause; the guardian can only pause.")]
+    UnauthorizedPauser,
+    #[msg("Fee exceeds the maximum allowed basis points.")]
+    FeeTooHigh,
+    #[msg("Signer is not a registered relayer.")]
+    UnauthorizedRelayer,
+    #[msg("Relayer is already registered.")]
+    RelayerAlreadyRegistered,
+    #[msg("Relayer is not registered.")]
+    RelayerNotRegistered,
+    #[msg("Relayer registry is full.")]
+    RelayerRegistryFull,
+    #[msg("Threshold must be between 1 and the number of registered relayers.")]
+    InvalidThreshold,
+    #[msg("Relayer has already attested to this trade.")]
+    DuplicateAttestation,
+    #[msg("Not enough relayer attestations for this trade.")]
+    QuorumNotReached,
+    #[msg("Missing or malformed Secp256k1 signature instruction.")]
+    InvalidSecp256k1Instruction,
+    #[msg("Signed message does not match the relayed trade.")]
+    EvmMessageMismatch,
+    #[msg("EVM signer is not registered.")]
+    UnknownEvmSigner,
+    #[msg("EVM signer is already registered.")]
+    EvmSignerAlreadyRegistered,
+    #[msg("EVM signer is not registered.")]
+    EvmSignerNotRegistered,
+    #[msg("EVM signer set is full.")]
+    EvmSignerSetFull,
+    #[msg("Signed EVM trades do not take relayer attestations.")]
+    SignedTradeAttestation,
+    #[msg("Missing or malformed Ed25519 signature instruction.")]
+    InvalidEd25519Instruction,
+    #[msg("Order signature does not match the maker or the order.")]
+    InvalidOrderSignature,
+    #[msg("Order nonce has already been used or cancelled.")]
+    NonceAlreadyUsed,
+    #[msg("Preimage does not match the hashlock.")]
+    InvalidPreimage,
+    #[msg("HTLC timelock has passed; it can only be refunded.")]
+    HtlcExpired,
+    #[msg("HTLC timelock has not passed yet.")]
+    HtlcNotExpired,
+    #[msg("Recipient does not match the HTLC.")]
+    InvalidRecipient,
+    #[msg("Reclaim waiting period has not passed yet.")]
+    ReclaimTooEarly,
+    #[msg("A buyer is already bound to this offer.")]
+    BuyerAlreadyBound,
+    #[msg("No buyer has been bound to this offer yet.")]
+    BuyerNotBound,
+    #[msg("Buyer does not match the one bound to this offer.")]
+    InvalidBuyer,
+    #[msg("Only the seller or a registered relayer can bind the buyer.")]
+    UnauthorizedBinder,
+    #[msg("Deposit does not match the relayed trade terms.")]
+    DepositMismatch,
+    #[msg("Offer is not in a state that allows this action.")]
+    InvalidStateTransition,
+    #[msg("The offer's escrow account was not provided.")]
+    MissingEscrow,
+    #[msg("Vault ledger does not match the offer's mint.")]
+    InvalidVaultLedger,
+    #[msg("Payout exceeds what the vault ledger owes.")]
+    PayoutExceedsLedger,
+    #[msg("Escrow accounts must be (offer, escrow) pairs of this mint.")]
+    InvalidEscrow,
+    #[msg("Escrow was passed more than once.")]
+    DuplicateEscrow,
+    #[msg("Account is not an open offer in a pre-escrow layout.")]
+    InvalidLegacyOffer,
+    #[msg("The legacy offer did not record what was deposited.")]
+    UnrecordedLegacyDeposit,
+    #[msg("The mint's vault ledger was not provided.")]
+    MissingVaultLedger,
+    #[msg("Escrow still belongs to an open offer.")]
+    EscrowInUse,
+    #[msg("Only the offer's designated taker can fill it.")]
+    UnauthorizedTaker,
 }